@@ -0,0 +1,8 @@
+-- 待汇总发送的通知事件
+CREATE TABLE IF NOT EXISTS notification_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_type TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    content TEXT NOT NULL,
+    create_time INTEGER NOT NULL
+);
@@ -0,0 +1,15 @@
+-- 邮件发送记录
+CREATE TABLE IF NOT EXISTS email_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    template TEXT NOT NULL,
+    api_key TEXT,
+    status TEXT NOT NULL,
+    smtp_response TEXT,
+    create_time INTEGER NOT NULL,
+    update_time INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_log_create_time ON email_log (create_time);
+CREATE INDEX IF NOT EXISTS idx_email_log_recipient ON email_log (recipient);
@@ -0,0 +1,15 @@
+-- 定时发送的邮件
+CREATE TABLE IF NOT EXISTS scheduled_email (
+    id TEXT PRIMARY KEY,
+    api_key TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    content TEXT NOT NULL,
+    send_at INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    error TEXT,
+    create_time INTEGER NOT NULL,
+    update_time INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_email_status_send_at ON scheduled_email (status, send_at);
@@ -0,0 +1,6 @@
+-- 邮件退订/屏蔽名单
+CREATE TABLE IF NOT EXISTS email_suppression (
+    email TEXT PRIMARY KEY,
+    reason TEXT NOT NULL,
+    create_time INTEGER NOT NULL
+);
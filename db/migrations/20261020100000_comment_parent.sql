@@ -0,0 +1,4 @@
+-- 评论回复关系
+ALTER TABLE comment ADD COLUMN parent_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_comment_parent_id ON comment (parent_id);
@@ -0,0 +1,3 @@
+-- 评论列表分页排序索引
+CREATE INDEX IF NOT EXISTS idx_comment_app_topic_time ON comment (app, topic, create_time, id);
+CREATE INDEX IF NOT EXISTS idx_comment_app_topic_like ON comment (app, topic, like, create_time, id);
@@ -0,0 +1,7 @@
+-- 评论审核状态：pending、approved、rejected、spam
+ALTER TABLE comment ADD COLUMN status TEXT NOT NULL DEFAULT 'approved';
+
+-- 应用审核模式：post（先发后审）、pre（先审后发）
+ALTER TABLE comment_apps ADD COLUMN moderation TEXT NOT NULL DEFAULT 'post';
+
+CREATE INDEX IF NOT EXISTS idx_comment_status_time ON comment (status, create_time);
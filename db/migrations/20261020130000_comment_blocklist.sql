@@ -0,0 +1,7 @@
+-- 评论关键词/正则屏蔽规则
+CREATE TABLE IF NOT EXISTS comment_blocklist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    is_regex INTEGER NOT NULL DEFAULT 0,
+    create_time INTEGER NOT NULL
+);
@@ -0,0 +1,9 @@
+-- 评论点赞记录，每个点赞者对同一评论只记一次
+CREATE TABLE IF NOT EXISTS comment_likes (
+    comment_id TEXT NOT NULL,
+    liker TEXT NOT NULL, -- u:会话验证的邮箱 或 ip:客户端IP哈希
+    create_time INTEGER NOT NULL,
+    PRIMARY KEY (comment_id, liker)
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_likes_liker ON comment_likes (liker);
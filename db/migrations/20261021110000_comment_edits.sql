@@ -0,0 +1,13 @@
+-- 评论作者编辑凭证（SHA-256哈希）和最后编辑时间
+ALTER TABLE comment ADD COLUMN edit_token_hash TEXT;
+ALTER TABLE comment ADD COLUMN edit_time INTEGER;
+
+-- 评论编辑历史，记录每次编辑前的内容
+CREATE TABLE IF NOT EXISTS comment_edits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    comment_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    edit_time INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_edits_comment_id ON comment_edits (comment_id);
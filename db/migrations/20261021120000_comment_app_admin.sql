@@ -0,0 +1,10 @@
+-- 评论应用管理：key只保存SHA-256哈希，轮换后旧key在宽限期内仍可使用
+ALTER TABLE comment_apps ADD COLUMN key_hash TEXT;
+ALTER TABLE comment_apps ADD COLUMN old_key_hash TEXT;
+ALTER TABLE comment_apps ADD COLUMN old_key_expire_time INTEGER;
+ALTER TABLE comment_apps ADD COLUMN disabled INTEGER NOT NULL DEFAULT 0;
+
+-- 应用设置：评论最大长度（字节）、通知邮箱（为空时通知管理员）
+ALTER TABLE comment_apps ADD COLUMN max_length INTEGER NOT NULL DEFAULT 1023;
+ALTER TABLE comment_apps ADD COLUMN notify_email TEXT;
+ALTER TABLE comment_apps ADD COLUMN create_time INTEGER;
@@ -0,0 +1,3 @@
+-- 应用设置：是否将评论按Markdown渲染为contentHtml、渲染时是否保留图片
+ALTER TABLE comment_apps ADD COLUMN markdown INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE comment_apps ADD COLUMN allow_images INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+-- 评论订阅的只读凭证（SHA-256哈希），与写入用的key分开
+ALTER TABLE comment_apps ADD COLUMN feed_token_hash TEXT;
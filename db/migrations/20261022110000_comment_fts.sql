@@ -0,0 +1,22 @@
+-- 评论全文索引，trigram分词以支持中文子串搜索（至少3个字符）
+CREATE VIRTUAL TABLE IF NOT EXISTS comment_fts USING fts5(
+    content,
+    comment_id UNINDEXED,
+    tokenize = 'trigram'
+);
+
+INSERT INTO comment_fts (content, comment_id) SELECT content, id FROM comment;
+
+-- 通过触发器与comment表保持同步
+CREATE TRIGGER IF NOT EXISTS comment_fts_insert AFTER INSERT ON comment BEGIN
+    INSERT INTO comment_fts (content, comment_id) VALUES (new.content, new.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS comment_fts_update AFTER UPDATE OF content ON comment BEGIN
+    DELETE FROM comment_fts WHERE comment_id = old.id;
+    INSERT INTO comment_fts (content, comment_id) VALUES (new.content, new.id);
+END;
+
+CREATE TRIGGER IF NOT EXISTS comment_fts_delete AFTER DELETE ON comment BEGIN
+    DELETE FROM comment_fts WHERE comment_id = old.id;
+END;
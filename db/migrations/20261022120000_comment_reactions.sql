@@ -0,0 +1,13 @@
+-- 应用可用的表情回应（JSON数组），为空时使用默认表情
+ALTER TABLE comment_apps ADD COLUMN reactions TEXT;
+
+-- 评论表情回应记录，每个访客对同一评论的同一表情只记一次
+CREATE TABLE IF NOT EXISTS comment_reactions (
+    comment_id TEXT NOT NULL,
+    reactor TEXT NOT NULL, -- u:会话验证的邮箱 或 ip:客户端IP哈希
+    reaction TEXT NOT NULL,
+    create_time INTEGER NOT NULL,
+    PRIMARY KEY (comment_id, reactor, reaction)
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_reactions_reactor ON comment_reactions (reactor);
@@ -0,0 +1,19 @@
+-- 评论话题登记表，记录话题标题、页面地址和锁定状态
+CREATE TABLE IF NOT EXISTS comment_topics (
+    app TEXT NOT NULL,
+    topic TEXT NOT NULL,
+    title TEXT,           -- 为空时显示话题本身
+    url TEXT,             -- 为空时根据话题和SITE_URL生成
+    locked INTEGER NOT NULL DEFAULT 0, -- 锁定后不能发表新评论
+    create_time INTEGER NOT NULL,
+    PRIMARY KEY (app, topic)
+);
+
+INSERT OR IGNORE INTO comment_topics (app, topic, create_time)
+    SELECT app, topic, MIN(create_time) FROM comment GROUP BY app, topic;
+
+-- 话题的第一条评论写入时自动登记
+CREATE TRIGGER IF NOT EXISTS comment_topics_insert AFTER INSERT ON comment BEGIN
+    INSERT OR IGNORE INTO comment_topics (app, topic, create_time)
+        VALUES (new.app, new.topic, new.create_time);
+END;
@@ -0,0 +1,6 @@
+-- 置顶时间，为空表示未置顶；置顶评论按置顶时间倒序排在最前
+ALTER TABLE comment ADD COLUMN pinned_time INTEGER;
+-- 是否由站长（管理员）发表，用于显示作者标识
+ALTER TABLE comment ADD COLUMN is_author INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX IF NOT EXISTS idx_comment_app_topic_pinned ON comment (app, topic, pinned_time) WHERE pinned_time IS NOT NULL;
@@ -0,0 +1,11 @@
+-- 评论举报记录，每个举报人对同一评论只记一次
+CREATE TABLE IF NOT EXISTS comment_reports (
+    comment_id TEXT NOT NULL,
+    reporter TEXT NOT NULL, -- u:会话验证的邮箱 或 ip:客户端IP哈希
+    reason TEXT NOT NULL,
+    create_time INTEGER NOT NULL,
+    resolved_time INTEGER, -- 管理员处理时间，为空表示待处理
+    PRIMARY KEY (comment_id, reporter)
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_reports_pending ON comment_reports (comment_id) WHERE resolved_time IS NULL;
@@ -0,0 +1,24 @@
+-- 应用是否开启邮箱验证身份
+ALTER TABLE comment_apps ADD COLUMN verify_identity INTEGER NOT NULL DEFAULT 0;
+-- 评论是否由已验证邮箱的用户发表
+ALTER TABLE comment ADD COLUMN verified INTEGER NOT NULL DEFAULT 0;
+
+-- 邮箱登录链接凭证，只保存哈希，使用一次后失效
+CREATE TABLE IF NOT EXISTS comment_login_tokens (
+    token_hash TEXT PRIMARY KEY,
+    app TEXT NOT NULL,
+    email TEXT NOT NULL,
+    create_time INTEGER NOT NULL,
+    expire_time INTEGER NOT NULL,
+    used_time INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_login_tokens_email ON comment_login_tokens (app, email, create_time);
+
+-- 已验证的邮箱身份，未验证的用户不能使用这些邮箱评论
+CREATE TABLE IF NOT EXISTS verified_identities (
+    app TEXT NOT NULL,
+    email TEXT NOT NULL, -- 小写
+    verify_time INTEGER NOT NULL,
+    PRIMARY KEY (app, email)
+);
@@ -0,0 +1,2 @@
+-- 应用的默认时区（IANA时区名），为空时使用服务默认时区
+ALTER TABLE comment_apps ADD COLUMN timezone TEXT;
@@ -0,0 +1,30 @@
+-- 评论事件Webhook订阅
+CREATE TABLE IF NOT EXISTS comment_webhooks (
+    id TEXT PRIMARY KEY,
+    app TEXT NOT NULL,
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL, -- 签名密钥，需要明文保存用于签名
+    events TEXT NOT NULL, -- 订阅的事件，JSON数组
+    enabled INTEGER NOT NULL DEFAULT 1,
+    create_time INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_webhooks_app ON comment_webhooks (app);
+
+-- Webhook投递记录，失败后按退避时间重试
+CREATE TABLE IF NOT EXISTS comment_webhook_deliveries (
+    id TEXT PRIMARY KEY,
+    webhook_id TEXT NOT NULL,
+    event TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending', -- pending, success, failed
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_time INTEGER NOT NULL,
+    response_status INTEGER,
+    error TEXT,
+    create_time INTEGER NOT NULL,
+    update_time INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_comment_webhook_deliveries_due ON comment_webhook_deliveries (status, next_attempt_time);
+CREATE INDEX IF NOT EXISTS idx_comment_webhook_deliveries_webhook ON comment_webhook_deliveries (webhook_id, create_time);
@@ -0,0 +1,5 @@
+-- 评论id全局唯一，导入等写入路径依赖此约束
+-- 历史数据中重复的id只保留最早写入的一条，否则无法建立唯一索引
+DELETE FROM comment WHERE rowid NOT IN (SELECT MIN(rowid) FROM comment GROUP BY id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_comment_id ON comment (id);
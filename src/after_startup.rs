@@ -4,7 +4,10 @@ use std::sync::Arc;
 use tokio::time::Duration;
 
 use crate::dao::blog;
+use crate::dao::email_log;
 use crate::util::email;
+use crate::util::notification::{self, NotificationEvent, notify};
+use crate::util::webhook;
 
 /// 启动前业务逻辑
 pub async fn after_startup(pool: &Arc<SqlitePool>) -> Result<()> {
@@ -33,6 +36,11 @@ pub async fn after_startup(pool: &Arc<SqlitePool>) -> Result<()> {
             interval.tick().await;
             if let Err(e) = clean_old_visits_task(&pool_for_cleanup).await {
                 eprintln!("❌ 清理旧访问记录失败: {}", e);
+                notify_job_failed(&pool_for_cleanup, "清理旧访问记录", &e.to_string()).await;
+            }
+            if let Err(e) = clean_old_email_logs_task(&pool_for_cleanup).await {
+                eprintln!("❌ 清理邮件发送记录失败: {}", e);
+                notify_job_failed(&pool_for_cleanup, "清理邮件发送记录", &e.to_string()).await;
             }
         }
     });
@@ -50,6 +58,52 @@ pub async fn after_startup(pool: &Arc<SqlitePool>) -> Result<()> {
         }
     });
 
+    // 启动通知汇总任务
+    let pool_for_digest = Arc::clone(pool);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(notification::digest_interval());
+
+        loop {
+            interval.tick().await;
+            match notification::send_digest(&pool_for_digest).await {
+                Ok(0) => {}
+                Ok(count) => println!("✅ 已发送通知汇总邮件，共 {} 条", count),
+                Err(e) => eprintln!("❌ 发送通知汇总邮件失败: {}", e),
+            }
+        }
+    });
+
+    // 启动定时邮件发送任务
+    let pool_for_scheduled = Arc::clone(pool);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = email::send_due_scheduled_emails(&pool_for_scheduled).await {
+                eprintln!("❌ 发送定时邮件失败: {}", e);
+                notify_job_failed(&pool_for_scheduled, "发送定时邮件", &e).await;
+            }
+        }
+    });
+
+    // 启动Webhook投递任务：有新投递时立即投递，并定时重试失败的投递
+    let pool_for_webhook = Arc::clone(pool);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = webhook::delivery_requested() => {}
+            }
+            if let Err(e) = webhook::deliver_due_webhooks(&pool_for_webhook).await {
+                eprintln!("❌ 投递Webhook失败: {}", e);
+                notify_job_failed(&pool_for_webhook, "投递Webhook", &e).await;
+            }
+        }
+    });
+
     // 发送启动通知邮件
     let start_notification = format!(
         "Rust后端服务已成功启动！\n\n版本：{}\n\n数据库表信息：\n{}",
@@ -60,9 +114,10 @@ pub async fn after_startup(pool: &Arc<SqlitePool>) -> Result<()> {
         Some("【Rust】后端服务启动通知".to_string()),
         start_notification,
         None,
-    );
+    )
+    .with_template("startup");
 
-    match email::send_email(email_config).await {
+    match email::send_email(pool.as_ref(), email_config).await {
         Ok(_) => {
             println!("✅ 已发送启动通知邮件");
         }
@@ -80,3 +135,31 @@ async fn clean_old_visits_task(pool: &Arc<SqlitePool>) -> Result<()> {
     blog::clean_old_visits(pool.as_ref()).await?;
     Ok(())
 }
+
+/// 清理过期邮件发送记录的任务，保留天数读取 `EMAIL_LOG_RETENTION_DAYS`，默认90天
+async fn clean_old_email_logs_task(pool: &Arc<SqlitePool>) -> Result<()> {
+    let days = std::env::var("EMAIL_LOG_RETENTION_DAYS")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(90);
+    let cutoff_time = chrono::Utc::now().timestamp() - days * 24 * 60 * 60;
+    let deleted = email_log::delete_email_logs_before(pool.as_ref(), cutoff_time).await?;
+    if deleted > 0 {
+        println!("✅ 已清理 {} 条过期邮件发送记录", deleted);
+    }
+    Ok(())
+}
+
+/// 通知管理员后台任务失败
+async fn notify_job_failed(pool: &Arc<SqlitePool>, job: &str, error: &str) {
+    if let Err(e) = notify(
+        pool.as_ref(),
+        NotificationEvent::JobFailed,
+        format!("【Rust】后台任务失败: {}", job),
+        format!("任务：{}\n错误：{}", job, error),
+    )
+    .await
+    {
+        eprintln!("❌ 发送任务失败通知失败: {}", e);
+    }
+}
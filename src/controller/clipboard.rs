@@ -15,7 +15,7 @@ use crate::dao::clipboard::{
     get_clipboard_by_id, get_clipboard_by_openid, insert_clipboard,
     update_clipboard_by_id, Clipboard, ClipboardResponse,
 };
-use crate::util::email::{EmailConfig, send_email};
+use crate::util::notification::{NotificationEvent, notify};
 use crate::util::uuid::generate_short_uuid;
 
 // 获取剪贴板内容的路径参数结构体
@@ -255,12 +255,14 @@ pub async fn get_by_wx_code(
                         match insert_clipboard(pool.as_ref(), &new_clipboard).await {
                             Ok(clipboard) => {
                                 // 发送邮件通知
-                                let email_config = EmailConfig::new(
-                                    Some("有新的用户注册了Clipboard服务".to_string()),
+                                if let Err(e) = notify(
+                                    pool.as_ref(),
+                                    NotificationEvent::NewClipboardUser,
+                                    "有新的用户注册了Clipboard服务".to_string(),
                                     "剪贴板服务".to_string(),
-                                    None,
-                                );
-                                if let Err(e) = send_email(email_config).await {
+                                )
+                                .await
+                                {
                                     eprintln!("Error sending email: {:?}", e);
                                 }
 
@@ -1,20 +1,42 @@
 use axum::{
-    extract::{Json as AxumJson, Query, State},
-    http::StatusCode,
-    response::{IntoResponse, Json},
+    extract::{ConnectInfo, Json as AxumJson, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
 };
-use chrono::{Local, TimeZone, Utc};
-use regex::Regex;
+use chrono::Utc;
 use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use uuid::Uuid;
 
 use super::ApiResponse;
+use crate::dao::blocklist::get_block_rules;
 use crate::dao::comment::{
-    get_comments_by_app_topic, insert_comment, update_comment_like, validate_app_key, Comment,
-    CommentResponse, ToResponse,
+    count_comments, count_comments_by_topics, get_comment_by_id, get_edit_token_hash,
+    get_liked_comment_ids, get_recent_topic_contents, get_thread_replies, insert_comment,
+    list_comments, get_pinned_comments, set_comment_like, update_comment_content, update_comments_status, Comment,
+    CommentCursor, CommentListOptions, CommentPage, CommentResponse, CommentSort,
+    PostCommentResponse, ToResponse, TopicCountResponse, STATUS_APPROVED, STATUS_DELETED,
+    STATUS_PENDING, STATUS_SPAM,
 };
-use crate::util::email::{EmailConfig, send_email};
+use crate::dao::comment_app::{get_app_by_key, CommentApp, MODERATION_PRE};
+use crate::dao::identity::is_verified_identity;
+use crate::dao::reaction::{
+    ReactionCount, ReactionSummary, get_comment_reaction_counts, get_reaction_counts,
+    get_visitor_reactions, toggle_reaction,
+};
+use crate::util::email::{EmailConfig, hide_email, send_email};
+use crate::util::markdown;
+use crate::dao::report::{CommentReport, insert_report};
+use crate::dao::topic::get_topic;
+use crate::util::notification::{NotificationEvent, notify, notify_reply, topic_link};
+use crate::util::session::verify_session;
+use crate::util::spam::{self, SpamContext};
+use crate::util::time::{TimeFormat, parse_timezone};
+use crate::util::token;
+use crate::util::webhook::{self, WebhookEvent};
 
 // 请求查询参数结构体
 #[derive(Debug, serde::Deserialize)]
@@ -22,40 +44,28 @@ pub struct CommentQuery {
     a: String, // app
     k: String, // key
     t: String, // topic
+    tree: Option<bool>,     // 是否以嵌套回复树返回
+    depth: Option<usize>,   // 回复树最大嵌套层数，默认3
+    limit: Option<i64>,     // 每页条数，指定时返回分页结果
+    cursor: Option<String>, // 分页游标，取自上一页的nextCursor
+    sort: Option<String>,   // 排序方式：newest（默认）、oldest、likes
+    u: Option<String>,      // 当前用户的登录会话，指定时返回likedByMe和myReactions，会话无效时按客户端IP识别
+    tz: Option<String>,     // 显示时间使用的时区，默认为应用时区
+    relative: Option<bool>, // 是否以相对时间显示createTime
 }
 
-// 格式化时间戳为字符串
-fn format_timestamp(ts: i64) -> String {
-    let dt = Local.timestamp_opt(ts, 0).unwrap();
-    dt.format("%Y/%m/%d %H:%M:%S").to_string()
-}
+// 回复树默认和最大嵌套层数
+const DEFAULT_TREE_DEPTH: usize = 3;
+const MAX_TREE_DEPTH: usize = 10;
 
-// 隐藏用户邮箱
-fn hide_email(user: &str) -> String {
-    lazy_static::lazy_static! {
-        static ref EMAIL_REGEX: Regex = Regex::new(r"^\S+@\w+(\.[\w]+)+").unwrap();
-    }
+// 每页最大条数
+const MAX_PAGE_LIMIT: i64 = 100;
 
-    if EMAIL_REGEX.is_match(user) {
-        if let Some(at_index) = user.rfind('@') {
-            if at_index > 1 {
-                let first_char = &user[0..1];
-                let asterisks = "*".repeat(at_index - 1);
-                let domain = &user[at_index..];
-                format!("{}{}{}", first_char, asterisks, domain)
-            } else {
-                user.to_string()
-            }
-        } else {
-            user.to_string()
-        }
-    } else {
-        user.to_string()
-    }
-}
+// 回复树中已删除或隐藏评论的占位内容
+const DELETED_PLACEHOLDER: &str = "该评论已删除";
 
 // 转换Comment为CommentResponse
-fn convert_to_response(comment: &Comment) -> CommentResponse {
+fn convert_to_response(comment: &Comment, time_format: &TimeFormat) -> CommentResponse {
     // 处理to字段
     let to = if let (Some(to_user), Some(to_content)) = (&comment.to_user, &comment.to_content) {
         Some(ToResponse {
@@ -71,16 +81,216 @@ fn convert_to_response(comment: &Comment) -> CommentResponse {
         content: comment.content.clone(),
         user: hide_email(&comment.user),
         like: comment.like,
-        create_time: format_timestamp(comment.create_time),
+        create_time: time_format.format(comment.create_time),
+        create_time_iso: time_format.iso(comment.create_time),
+        create_timestamp: comment.create_time,
         to,
+        parent_id: comment.parent_id.clone(),
+        replies: None,
+        liked_by_me: None,
+        edited: comment.edit_time.is_some(),
+        pinned: comment.pinned_time.is_some(),
+        author: comment.is_author,
+        verified: comment.verified,
+        deleted: false,
+        content_html: None,
+        reactions: Vec::new(),
+        my_reactions: None,
+    }
+}
+
+// 转换回复树中的评论：未公开的评论只保留id、父评论和时间作为占位
+fn convert_to_tree_node(comment: &Comment, time_format: &TimeFormat) -> CommentResponse {
+    let mut response = convert_to_response(comment, time_format);
+    if comment.status != STATUS_APPROVED {
+        response.content = DELETED_PLACEHOLDER.to_string();
+        response.user = String::new();
+        response.like = 0;
+        response.to = None;
+        response.edited = false;
+        response.pinned = false;
+        response.author = false;
+        response.verified = false;
+        response.deleted = true;
+    }
+    response
+}
+
+// 应用开启Markdown时渲染评论内容
+fn render_markdown(comments: &mut [CommentResponse], app: &CommentApp) {
+    if !app.markdown {
+        return;
+    }
+    for comment in comments.iter_mut().filter(|c| !c.deleted) {
+        comment.content_html = Some(markdown::render(&comment.content, app.allow_images));
+    }
+}
+
+// 信任的代理头，环境变量 `TRUSTED_PROXY_HEADER`（如 X-Real-IP），未设置时使用连接地址
+fn trusted_proxy_header() -> Option<String> {
+    env::var("TRUSTED_PROXY_HEADER")
+        .ok()
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+}
+
+// 站长邮箱，环境变量 `COMMENT_OWNER_EMAILS`（逗号分隔）
+fn owner_emails() -> String {
+    env::var("COMMENT_OWNER_EMAILS").unwrap_or_default()
+}
+
+// 判断已验证的邮箱是否为站长邮箱
+fn is_owner_email(owner_emails: &str, email: &str) -> bool {
+    owner_emails
+        .split(',')
+        .map(str::trim)
+        .any(|owner| !owner.is_empty() && owner.eq_ignore_ascii_case(email))
+}
+
+// 客户端IP：配置了信任的代理头时从该头获取（多个值时取代理追加的最后一个），否则使用连接地址
+fn client_ip(headers: &HeaderMap, addr: &SocketAddr, trusted_header: Option<&str>) -> String {
+    trusted_header
+        .and_then(|name| headers.get(name))
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.rsplit(',').next())
+        .map(|ip| ip.trim())
+        .filter(|ip| !ip.is_empty())
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| addr.ip().to_string())
+}
+
+// 访客标识（用于点赞、表情回应和举报去重）：带有本应用有效登录会话时为验证过的邮箱，否则为客户端IP的哈希
+// 不使用客户端提交的用户名，也不保存IP原文；哈希不依赖服务端密钥，重启后保持不变
+fn visitor_key(
+    app_id: &str,
+    session: Option<&str>,
+    headers: &HeaderMap,
+    addr: &SocketAddr,
+) -> String {
+    if let Some(session) = session.and_then(|s| verify_session(s, Utc::now().timestamp()))
+        && session.app == app_id
+    {
+        return format!("u:{}", session.email);
+    }
+    let ip = client_ip(headers, addr, trusted_proxy_header().as_deref());
+    format!("ip:{}", token::hash_token(&format!("visitor-ip:{}", ip)))
+}
+
+// 标记当前用户点赞过的评论
+fn mark_liked(comments: &mut [CommentResponse], liked: &HashSet<String>) {
+    for comment in comments.iter_mut().filter(|c| !c.deleted) {
+        comment.liked_by_me = Some(liked.contains(&comment.id));
+    }
+}
+
+// 按应用配置的表情顺序汇总回应数，已从配置中移除的表情不再返回
+fn summarize_reactions(counts: &HashMap<&str, i64>, reaction_set: &[String]) -> Vec<ReactionSummary> {
+    reaction_set
+        .iter()
+        .filter_map(|reaction| {
+            counts.get(reaction.as_str()).map(|&count| ReactionSummary {
+                reaction: reaction.clone(),
+                count,
+            })
+        })
+        .collect()
+}
+
+// 为评论填充表情回应数，mine为当前访客的回应(评论id, 表情)
+fn attach_reactions(
+    comments: &mut [CommentResponse],
+    counts: &[ReactionCount],
+    mine: Option<&[(String, String)]>,
+    reaction_set: &[String],
+) {
+    let mut by_comment: HashMap<&str, HashMap<&str, i64>> = HashMap::new();
+    for count in counts {
+        by_comment
+            .entry(count.comment_id.as_str())
+            .or_default()
+            .insert(count.reaction.as_str(), count.count);
+    }
+
+    for comment in comments.iter_mut().filter(|c| !c.deleted) {
+        if let Some(counts) = by_comment.get(comment.id.as_str()) {
+            comment.reactions = summarize_reactions(counts, reaction_set);
+        }
+        if let Some(mine) = mine {
+            comment.my_reactions = Some(
+                mine.iter()
+                    .filter(|(id, reaction)| *id == comment.id && reaction_set.contains(reaction))
+                    .map(|(_, reaction)| reaction.clone())
+                    .collect(),
+            );
+        }
+    }
+}
+
+// 将顶层评论和按时间正序排列的回复组装为回复树
+//
+// 顶层评论保持原有顺序；超过depth层的回复平铺到第depth层评论的replies中。
+// 已删除或隐藏的评论仍有公开回复时保留为占位节点，没有公开回复时从树中移除。
+fn build_comment_tree(
+    roots: Vec<CommentResponse>,
+    replies: Vec<CommentResponse>,
+    depth: usize,
+) -> Vec<CommentResponse> {
+    let mut children: HashMap<String, Vec<CommentResponse>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_id) = reply.parent_id.clone() {
+            children.entry(parent_id).or_default().push(reply);
+        }
+    }
+
+    roots
+        .into_iter()
+        .filter_map(|root| attach_replies(root, &mut children, 1, depth))
+        .collect()
+}
+
+// 为评论挂载回复，level为回复所在层数；没有公开回复的占位节点返回None
+fn attach_replies(
+    mut comment: CommentResponse,
+    children: &mut HashMap<String, Vec<CommentResponse>>,
+    level: usize,
+    depth: usize,
+) -> Option<CommentResponse> {
+    let Some(direct) = children.remove(&comment.id) else {
+        return (!comment.deleted).then_some(comment);
+    };
+
+    let replies: Vec<CommentResponse> = if level < depth {
+        direct
+            .into_iter()
+            .filter_map(|reply| attach_replies(reply, children, level + 1, depth))
+            .collect()
+    } else {
+        // 达到最大层数，将所有后代平铺，平铺后占位节点没有意义，直接去掉
+        let mut flat = Vec::new();
+        let mut stack: Vec<CommentResponse> = direct.into_iter().rev().collect();
+        while let Some(reply) = stack.pop() {
+            if let Some(grand) = children.remove(&reply.id) {
+                stack.extend(grand.into_iter().rev());
+            }
+            if !reply.deleted {
+                flat.push(reply);
+            }
+        }
+        flat
+    };
+
+    if replies.is_empty() {
+        return (!comment.deleted).then_some(comment);
     }
+    comment.replies = Some(replies);
+    Some(comment)
 }
 
 // POST评论请求体结构体
 #[derive(Debug, serde::Deserialize)]
 pub struct PostCommentBody {
     #[serde(rename = "type")]
-    c_type: i32, // 评论类型，0.评论，1.点赞
+    c_type: i32, // 评论类型，0.评论，1.点赞，2.取消点赞
     content: Option<String>, // 评论内容
     app: String,             // 应用ID
     key: String,             // 应用密钥
@@ -89,11 +299,17 @@ pub struct PostCommentBody {
     to: Option<String>,      // 回复对象用户
     #[serde(rename = "toId")]
     to_id: Option<String>, // 回复对象ID
+    website: Option<String>, // 蜜罐字段，前端隐藏，正常用户不会填写
+    session: Option<String>, // 邮箱验证后获得的会话令牌
+    #[serde(rename = "withEditToken", default)]
+    with_edit_token: bool, // 为true时返回{_id, editToken}，否则保持原来只返回评论id
 }
 
 // 获取评论列表的处理函数
 pub async fn get_comments(
     State(pool): State<Arc<SqlitePool>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     Query(query): Query<CommentQuery>,
 ) -> impl IntoResponse {
     // 验证查询参数
@@ -119,31 +335,72 @@ pub async fn get_comments(
             .into_response();
     }
 
+    // 验证时区
+    if let Some(tz) = query.tz.as_deref().filter(|tz| !tz.is_empty())
+        && parse_timezone(tz).is_none()
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("unknown timezone: {}", tz))),
+        )
+            .into_response();
+    }
+
+    // 验证排序方式和分页游标
+    let sort = match query.sort.as_deref() {
+        None | Some("") => CommentSort::Newest,
+        Some(sort) => match CommentSort::parse(sort) {
+            Some(sort) => sort,
+            None => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error(
+                        "sort must be newest, oldest or likes".to_string(),
+                    )),
+                )
+                    .into_response();
+            }
+        },
+    };
+    let cursor = match query.cursor.as_deref() {
+        None | Some("") => None,
+        Some(cursor) => match CommentCursor::decode(cursor) {
+            Some(cursor) if cursor.sort == sort => Some(cursor),
+            _ => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error("invalid cursor".to_string())),
+                )
+                    .into_response();
+            }
+        },
+    };
+
     // 验证app和key
-    match validate_app_key(pool.as_ref(), &query.a, &query.k).await {
-        Ok(true) => {
-            // 获取评论列表
-            match get_comments_by_app_topic(pool.as_ref(), &query.a, &query.t).await {
-                Ok(comments) => {
-                    // 转换为响应格式
-                    let response_comments: Vec<CommentResponse> =
-                        comments.iter().map(convert_to_response).collect();
-
-                    Json(ApiResponse::data_success(response_comments)).into_response()
-                }
-                Err(e) => {
-                    eprintln!("Error getting comments: {:?}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ApiResponse::<()>::error(
-                            "Failed to get comments".to_string(),
-                        )),
-                    )
-                        .into_response()
-                }
+    match get_app_by_key(pool.as_ref(), &query.a, &query.k).await {
+        Ok(Some(app)) => match load_comments(
+            pool.as_ref(),
+            &app,
+            &query,
+            &visitor_key(&query.a, query.u.as_deref(), &headers, &addr),
+            sort,
+            cursor,
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("Error getting comments: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to get comments".to_string(),
+                    )),
+                )
+                    .into_response()
             }
-        }
-        Ok(false) => (
+        },
+        Ok(None) => (
             StatusCode::UNAUTHORIZED,
             Json(ApiResponse::<()>::error("Unauthorized".to_string())),
         )
@@ -161,13 +418,125 @@ pub async fn get_comments(
     }
 }
 
+// 查询评论列表并组装响应：指定limit时返回分页结果，否则返回全部评论
+async fn load_comments(
+    pool: &SqlitePool,
+    app: &CommentApp,
+    query: &CommentQuery,
+    visitor: &str,
+    sort: CommentSort,
+    cursor: Option<CommentCursor>,
+) -> Result<Response, sqlx::Error> {
+    let tree = query.tree.unwrap_or(false);
+    let limit = query.limit.map(|l| l.clamp(1, MAX_PAGE_LIMIT));
+    let time_format = TimeFormat::new(
+        query
+            .tz
+            .as_deref()
+            .and_then(parse_timezone)
+            .unwrap_or_else(|| app.time_zone()),
+        query.relative.unwrap_or(false),
+        Utc::now().timestamp(),
+    );
+
+    // 置顶评论只在第一页最前面返回
+    let first_page = cursor.is_none();
+
+    // 多取一条用于判断是否还有下一页
+    let options = CommentListOptions {
+        sort,
+        cursor,
+        limit: limit.map(|l| l + 1),
+        roots_only: tree,
+    };
+    let mut comments = list_comments(pool, &query.a, &query.t, &options).await?;
+
+    let mut next_cursor = None;
+    if let Some(limit) = limit
+        && comments.len() as i64 > limit
+    {
+        comments.truncate(limit as usize);
+        next_cursor = comments
+            .last()
+            .map(|c| CommentCursor::from_comment(sort, c).encode());
+    }
+    if first_page {
+        let mut pinned = get_pinned_comments(pool, &query.a, &query.t, tree).await?;
+        pinned.append(&mut comments);
+        comments = pinned;
+    }
+
+    // 转换为响应格式，回复树只加载本页顶层评论下的回复
+    let mut response_comments: Vec<CommentResponse> = comments
+        .iter()
+        .map(|c| convert_to_tree_node(c, &time_format))
+        .collect();
+    let mut replies: Vec<CommentResponse> = Vec::new();
+    if tree {
+        let root_ids: Vec<String> = comments.iter().map(|c| c.id.clone()).collect();
+        replies = get_thread_replies(pool, &root_ids)
+            .await?
+            .iter()
+            .map(|c| convert_to_tree_node(c, &time_format))
+            .collect();
+    }
+
+    // 指定了当前用户时标记当前用户（会话无效时为当前IP）是否已点赞和已回应的表情
+    let mut mine = None;
+    if query.u.as_deref().is_some_and(|u| !u.trim().is_empty()) {
+        let liked = get_liked_comment_ids(pool, &query.a, &query.t, visitor).await?;
+        mark_liked(&mut response_comments, &liked);
+        mark_liked(&mut replies, &liked);
+        mine = Some(get_visitor_reactions(pool, &query.a, &query.t, visitor).await?);
+    }
+    let counts = get_reaction_counts(pool, &query.a, &query.t).await?;
+    let reaction_set = app.reaction_set();
+    attach_reactions(&mut response_comments, &counts, mine.as_deref(), &reaction_set);
+    attach_reactions(&mut replies, &counts, mine.as_deref(), &reaction_set);
+    render_markdown(&mut response_comments, app);
+    render_markdown(&mut replies, app);
+
+    if tree {
+        let depth = query
+            .depth
+            .unwrap_or(DEFAULT_TREE_DEPTH)
+            .clamp(1, MAX_TREE_DEPTH);
+        response_comments = build_comment_tree(response_comments, replies, depth);
+    }
+
+    if limit.is_none() {
+        return Ok(Json(ApiResponse::data_success(response_comments)).into_response());
+    }
+
+    let total = count_comments(pool, &query.a, &query.t, tree).await?;
+    Ok(Json(ApiResponse::data_success(CommentPage {
+        comments: response_comments,
+        total,
+        next_cursor,
+    }))
+    .into_response())
+}
+
+// 校验评论内容，max_length为应用设置的最大字节数，不合法时返回错误信息
+fn content_error(content: Option<&str>, max_length: i64) -> Option<String> {
+    match content {
+        None | Some("") => Some("内容不能为空".to_string()),
+        Some(content) if content.len() as i64 > max_length => {
+            Some(format!("内容不能超过{}字节", max_length))
+        }
+        _ => None,
+    }
+}
+
 // 提交评论的处理函数
 pub async fn post_comment(
     State(pool): State<Arc<SqlitePool>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
     AxumJson(body): AxumJson<PostCommentBody>,
 ) -> impl IntoResponse {
     // 验证评论类型
-    if body.c_type < 0 || body.c_type > 1 {
+    if body.c_type < 0 || body.c_type > 2 {
         return (
             StatusCode::BAD_REQUEST,
             Json(ApiResponse::<()>::error("评论类型不合法".to_string())),
@@ -175,132 +544,1248 @@ pub async fn post_comment(
             .into_response();
     }
 
+    // 验证app和key
+    match get_app_by_key(pool.as_ref(), &body.app, &body.key).await {
+        Ok(Some(app)) => match body.c_type {
+            // 添加新评论
+            0 => create_comment(pool.as_ref(), &app, &body).await,
+            // 点赞评论
+            1 => {
+                let liker = visitor_key(&app.id, body.session.as_deref(), &headers, &addr);
+                like_comment(pool.as_ref(), &app, &body, &liker, true).await
+            }
+            // 取消点赞
+            2 => {
+                let liker = visitor_key(&app.id, body.session.as_deref(), &headers, &addr);
+                like_comment(pool.as_ref(), &app, &body, &liker, false).await
+            }
+            _ => (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error("暂不支持".to_string())),
+            )
+                .into_response(),
+        },
+        Ok(None) => (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate app key".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 发表评论的响应：兼容旧客户端，默认只返回评论id，要求了编辑凭证时返回{_id, editToken}
+fn post_comment_response(id: String, edit_token: Option<String>) -> Response {
+    match edit_token {
+        Some(edit_token) => {
+            Json(ApiResponse::data_success(PostCommentResponse { id, edit_token })).into_response()
+        }
+        None => Json(ApiResponse::data_success(id)).into_response(),
+    }
+}
+
+// 添加新评论
+async fn create_comment(pool: &SqlitePool, app: &CommentApp, body: &PostCommentBody) -> Response {
     // 验证评论内容
-    if body.c_type == 0 {
-        if body.content.as_ref().map_or(true, |s| s.is_empty()) {
+    if let Some(message) = content_error(body.content.as_deref(), app.max_length) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    // 已锁定的话题不能发表新评论
+    let topic = match get_topic(pool, &body.app, &body.topic).await {
+        Ok(Some(topic)) if topic.locked => {
             return (
-                StatusCode::BAD_REQUEST,
-                Json(ApiResponse::<()>::error("内容不能为空".to_string())),
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error("话题已锁定，不能发表评论".to_string())),
+            )
+                .into_response();
+        }
+        Ok(topic) => topic,
+        Err(e) => {
+            eprintln!("Error getting comment topic: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment topic".to_string(),
+                )),
             )
                 .into_response();
         }
-        if let Some(content) = &body.content {
-            if content.len() > 1023 {
+    };
+
+    // 开启邮箱验证的应用：带有效会话时以验证过的邮箱评论，未验证的用户不能使用已验证的邮箱
+    // 用户标识按登录邮箱的规则去空格并转小写，校验和保存使用同一个值
+    let user = if app.verify_identity {
+        body.user.trim().to_lowercase()
+    } else {
+        body.user.clone()
+    };
+    let mut session = None;
+    if app.verify_identity {
+        match body.session.as_deref().filter(|s| !s.is_empty()) {
+            Some(token) => match verify_session(token, Utc::now().timestamp()) {
+                Some(verified) if verified.app == app.id => session = Some(verified),
+                _ => {
+                    return (
+                        StatusCode::UNAUTHORIZED,
+                        Json(ApiResponse::<()>::error(
+                            "登录已失效，请重新验证邮箱".to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+            },
+            None => match is_verified_identity(pool, &app.id, &user).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    return (
+                        StatusCode::FORBIDDEN,
+                        Json(ApiResponse::<()>::error(
+                            "该邮箱已验证，请先验证邮箱再评论".to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+                Err(e) => {
+                    eprintln!("Error checking verified identity: {:?}", e);
+                    return (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        Json(ApiResponse::<()>::error(
+                            "Failed to check identity".to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+            },
+        }
+    }
+    let verified = session.is_some();
+    // 以站长邮箱验证登录时作为站长评论
+    let is_author = session
+        .as_ref()
+        .is_some_and(|s| is_owner_email(&owner_emails(), &s.email));
+    let user = session.map_or(user, |s| s.email);
+
+    let content = body.content.as_ref().unwrap().clone();
+    let mut to_user = body.to.clone();
+    let mut to_content = None;
+    let mut parent_id = None;
+
+    // 回复评论：根据toId获取被回复评论，必须是同一应用和话题下已公开的评论
+    if let Some(to_id) = body.to_id.as_ref().filter(|id| !id.is_empty()) {
+        match get_comment_by_id(pool, to_id).await {
+            Ok(Some(parent)) if parent.status == STATUS_APPROVED => {
+                if parent.app != body.app || parent.topic != body.topic {
+                    return (
+                        StatusCode::BAD_REQUEST,
+                        Json(ApiResponse::<()>::error(
+                            "回复的评论不属于该话题".to_string(),
+                        )),
+                    )
+                        .into_response();
+                }
+                to_user = Some(parent.user);
+                to_content = Some(parent.content);
+                parent_id = Some(parent.id);
+            }
+            Ok(_) => {
                 return (
                     StatusCode::BAD_REQUEST,
-                    Json(ApiResponse::<()>::error("内容不能超过1000个字".to_string())),
+                    Json(ApiResponse::<()>::error("回复的评论不存在".to_string())),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                eprintln!("Error getting parent comment: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to get parent comment".to_string(),
+                    )),
                 )
                     .into_response();
             }
         }
     }
 
-    // 验证app和key
-    match validate_app_key(pool.as_ref(), &body.app, &body.key).await {
-        Ok(true) => {
-            match body.c_type {
-                // 添加新评论
-                0 => {
-                    let content = body.content.as_ref().unwrap().clone();
-                    let to_user = body.to.clone();
-                    let to_content = None; // 这里可以根据toId获取被回复评论的内容，目前暂时设为None
-
-                    // 创建新评论
-                    let comment = Comment {
-                        id: Uuid::new_v4().to_string(),
-                        app: body.app.clone(),
-                        topic: body.topic.clone(),
-                        content,
-                        create_time: Utc::now().timestamp(),
-                        user: body.user.clone(),
-                        like: 0,
-                        to_user,
-                        to_content,
-                    };
-
-                    // 插入评论
-                    match insert_comment(pool.as_ref(), &comment).await {
-                        Ok(inserted_id) => {
-                            // 发送邮件通知
-                            let email_content = format!(
-                                "评论已保存: {} - {}\n{}",
-                                comment.app,
-                                comment.topic,
-                                serde_json::to_string_pretty(&comment).unwrap_or_default()
-                            );
-
-                            if let Err(e) = send_email(EmailConfig::new(
-                                Some(format!("新评论通知: {} - {}", comment.app, comment.topic)),
-                                email_content,
-                                None,
-                            ))
-                            .await
-                            {
-                                eprintln!("Failed to send email: {:?}", e);
-                            }
+    // 创建新评论
+    let mut comment = Comment {
+        id: Uuid::new_v4().to_string(),
+        app: body.app.clone(),
+        topic: body.topic.clone(),
+        content,
+        create_time: Utc::now().timestamp(),
+        user,
+        like: 0,
+        to_user,
+        to_content,
+        parent_id,
+        status: STATUS_PENDING.to_string(),
+        edit_time: None,
+        pinned_time: None,
+        is_author,
+        verified,
+    };
 
-                            Json(ApiResponse::data_success(inserted_id)).into_response()
-                        }
-                        Err(e) => {
-                            eprintln!("Error inserting comment: {:?}", e);
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(ApiResponse::<()>::error(
-                                    "Failed to insert comment".to_string(),
-                                )),
-                            )
-                                .into_response()
-                        }
-                    }
-                }
-                // 点赞评论
-                1 => {
-                    if body.to_id.is_none() || body.to_id.as_ref().unwrap().is_empty() {
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(ApiResponse::<()>::error("toId required".to_string())),
-                        )
-                            .into_response();
-                    }
+    // 先审后发的应用，新评论进入待审核状态；垃圾评论检测得分超过阈值的标记为垃圾评论
+    // 站长评论直接公开
+    let status = if is_author {
+        STATUS_APPROVED
+    } else if is_spam_comment(pool, &comment, body.website.as_deref()).await {
+        STATUS_SPAM
+    } else if app.moderation == MODERATION_PRE {
+        STATUS_PENDING
+    } else {
+        STATUS_APPROVED
+    };
+    comment.status = status.to_string();
 
-                    let comment_id = body.to_id.as_ref().unwrap();
-                    match update_comment_like(pool.as_ref(), comment_id).await {
-                        Ok(modified_count) => {
-                            Json(ApiResponse::data_success(modified_count)).into_response()
-                        }
-                        Err(e) => {
-                            eprintln!("Error updating comment like: {:?}", e);
-                            (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                Json(ApiResponse::<()>::error(
-                                    "Failed to update comment like".to_string(),
-                                )),
-                            )
-                                .into_response()
+    // 调用方要求时生成作者编辑凭证，只保存哈希
+    let edit_token = body.with_edit_token.then(token::random_token);
+    let edit_token_hash = edit_token.as_deref().map(token::hash_token);
+
+    // 插入评论
+    match insert_comment(pool, &comment, edit_token_hash.as_deref()).await {
+        Ok(inserted_id) if comment.status == STATUS_SPAM => {
+            // 垃圾评论不发送通知，照常返回成功，避免发送方据此调整内容
+            post_comment_response(inserted_id, edit_token)
+        }
+        Ok(inserted_id) => {
+            // 发送邮件通知
+            let (title, url) = topic_link(&comment.topic, topic.as_ref());
+            let email_content = format!(
+                "评论已保存: {} - {}\n{}\n{}",
+                comment.app,
+                title,
+                url,
+                serde_json::to_string_pretty(&comment).unwrap_or_default()
+            );
+            let subject = if comment.status == STATUS_PENDING {
+                format!("待审核评论: {} - {}", comment.app, title)
+            } else {
+                format!("新评论通知: {} - {}", comment.app, title)
+            };
+
+            if let Err(e) = notify_comment(pool, app, subject, email_content).await {
+                eprintln!("Failed to send email: {:?}", e);
+            }
+
+            // 通知被回复的评论作者
+            if let Err(e) = notify_reply(pool, &comment).await {
+                eprintln!("Failed to send reply notification: {:?}", e);
+            }
+            if let Err(e) = webhook::dispatch(pool, WebhookEvent::Created, &comment).await {
+                eprintln!("Failed to dispatch webhook: {:?}", e);
+            }
+
+            post_comment_response(inserted_id, edit_token)
+        }
+        Err(e) => {
+            eprintln!("Error inserting comment: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to insert comment".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 新评论通知：应用设置了通知邮箱时直接通知该邮箱，否则通知管理员
+async fn notify_comment(
+    pool: &SqlitePool,
+    app: &CommentApp,
+    subject: String,
+    content: String,
+) -> Result<(), String> {
+    match &app.notify_email {
+        Some(notify_email) => {
+            send_email(
+                pool,
+                EmailConfig::new(Some(subject), content, Some(notify_email.clone()))
+                    .with_template("notification"),
+            )
+            .await
+        }
+        None => notify(pool, NotificationEvent::NewComment, subject, content).await,
+    }
+}
+
+// 对将要保存的评论运行垃圾评论过滤链，屏蔽规则按最终保存的用户标识匹配，查询失败时不拦截评论
+async fn is_spam_comment(pool: &SqlitePool, comment: &Comment, honeypot: Option<&str>) -> bool {
+    let block_rules = get_block_rules(pool).await.unwrap_or_else(|e| {
+        eprintln!("Error getting block rules: {:?}", e);
+        Vec::new()
+    });
+    // 重复检测只比较同话题24小时内的评论
+    let since = Utc::now().timestamp() - 24 * 3600;
+    let recent_contents =
+        get_recent_topic_contents(pool, &comment.app, &comment.topic, since, &comment.id)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting recent comments: {:?}", e);
+                Vec::new()
+            });
+
+    let ctx = SpamContext {
+        content: &comment.content,
+        user: &comment.user,
+        honeypot,
+        recent_contents: &recent_contents,
+        block_rules: &block_rules,
+    };
+    let verdict = spam::evaluate(&spam::default_filters(), &ctx);
+    if verdict.is_spam() {
+        println!(
+            "Comment marked as spam: {} - {} score={} [{}]",
+            comment.app,
+            comment.topic,
+            verdict.score,
+            verdict.summary()
+        );
+        return true;
+    }
+    false
+}
+
+// 点赞或取消点赞评论，重复操作不改变点赞数，返回最新点赞数
+async fn like_comment(
+    pool: &SqlitePool,
+    app: &CommentApp,
+    body: &PostCommentBody,
+    liker: &str,
+    liked: bool,
+) -> Response {
+    if body.to_id.is_none() || body.to_id.as_ref().unwrap().is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("toId required".to_string())),
+        )
+            .into_response();
+    }
+
+    let comment_id = body.to_id.as_ref().unwrap();
+    match set_comment_like(pool, &app.id, comment_id, liker, liked, Utc::now().timestamp()).await {
+        Ok(Some((like, changed))) => {
+            // 新增点赞时通知Webhook
+            if liked && changed {
+                match get_comment_by_id(pool, comment_id).await {
+                    Ok(Some(comment)) => {
+                        if let Err(e) =
+                            webhook::dispatch(pool, WebhookEvent::Liked, &comment).await
+                        {
+                            eprintln!("Failed to dispatch webhook: {:?}", e);
                         }
                     }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Error getting liked comment: {:?}", e),
                 }
-                _ => (
-                    StatusCode::BAD_REQUEST,
-                    Json(ApiResponse::<()>::error("暂不支持".to_string())),
-                )
-                    .into_response(),
             }
+            Json(ApiResponse::data_success(like)).into_response()
         }
-        Ok(false) => (
-            StatusCode::UNAUTHORIZED,
-            Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+        Ok(None) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("评论不存在".to_string())),
         )
             .into_response(),
         Err(e) => {
-            eprintln!("Error validating app key: {:?}", e);
+            eprintln!("Error updating comment like: {:?}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 Json(ApiResponse::<()>::error(
-                    "Failed to validate app key".to_string(),
+                    "Failed to update comment like".to_string(),
                 )),
             )
                 .into_response()
         }
     }
 }
+
+// 表情回应请求体
+#[derive(Debug, serde::Deserialize)]
+pub struct ReactionBody {
+    app: String,             // 应用ID
+    key: String,             // 应用密钥
+    reaction: String,        // 表情，必须是应用配置的表情之一，按用户或客户端IP去重
+    session: Option<String>, // 邮箱验证后获得的会话令牌，有效时按用户去重
+}
+
+// 表情回应响应
+#[derive(Debug, serde::Serialize)]
+pub struct ReactionResponse {
+    reacted: bool, // 切换后当前访客是否已回应该表情
+    reactions: Vec<ReactionSummary>,
+}
+
+// 切换评论表情回应的处理函数：已回应则取消，否则添加
+pub async fn react_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumJson(body): AxumJson<ReactionBody>,
+) -> impl IntoResponse {
+    // 验证app和key
+    let app = match get_app_by_key(pool.as_ref(), &body.app, &body.key).await {
+        Ok(Some(app)) => app,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate app key".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let reaction_set = app.reaction_set();
+    if !reaction_set.contains(&body.reaction) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("不支持的表情".to_string())),
+        )
+            .into_response();
+    }
+    let reactor = visitor_key(&app.id, body.session.as_deref(), &headers, &addr);
+
+    let now = Utc::now().timestamp();
+    let reacted = match toggle_reaction(pool.as_ref(), &app.id, &id, &reactor, &body.reaction, now).await {
+        Ok(Some(reacted)) => reacted,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("评论不存在".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error toggling comment reaction: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment reaction".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    match get_comment_reaction_counts(pool.as_ref(), &id).await {
+        Ok(counts) => {
+            let counts: HashMap<&str, i64> = counts
+                .iter()
+                .map(|c| (c.reaction.as_str(), c.count))
+                .collect();
+            Json(ApiResponse::data_success(ReactionResponse {
+                reacted,
+                reactions: summarize_reactions(&counts, &reaction_set),
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            eprintln!("Error getting comment reactions: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment reactions".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 举报理由最大长度（字符）
+const MAX_REPORT_REASON_LENGTH: usize = 500;
+
+// 举报评论请求体
+#[derive(Debug, serde::Deserialize)]
+pub struct ReportBody {
+    app: String,             // 应用ID
+    key: String,             // 应用密钥
+    reason: String,          // 举报理由，按用户或客户端IP去重
+    session: Option<String>, // 邮箱验证后获得的会话令牌，有效时按用户去重
+}
+
+// 自动隐藏评论的举报数阈值，环境变量 `COMMENT_REPORT_THRESHOLD`，默认3
+fn report_threshold() -> i64 {
+    env::var("COMMENT_REPORT_THRESHOLD")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|t| *t > 0)
+        .unwrap_or(3)
+}
+
+// 举报评论的处理函数，同一举报人重复举报不计数，评论收到第一条举报时通知管理员
+pub async fn report_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    AxumJson(body): AxumJson<ReportBody>,
+) -> impl IntoResponse {
+    // 验证app和key
+    let app = match get_app_by_key(pool.as_ref(), &body.app, &body.key).await {
+        Ok(Some(app)) => app,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate app key".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let reason = body.reason.trim();
+    if reason.is_empty() || reason.chars().count() > MAX_REPORT_REASON_LENGTH {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!(
+                "举报理由不能为空且不能超过{}字",
+                MAX_REPORT_REASON_LENGTH
+            ))),
+        )
+            .into_response();
+    }
+    let reporter = visitor_key(&app.id, body.session.as_deref(), &headers, &addr);
+
+    let report = CommentReport {
+        comment_id: id,
+        reporter,
+        reason: reason.to_string(),
+        create_time: Utc::now().timestamp(),
+    };
+    let outcome = match insert_report(pool.as_ref(), &app.id, &report, report_threshold()).await {
+        Ok(Some(outcome)) => outcome,
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("评论不存在".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error inserting comment report: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to report comment".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    // 自动隐藏相当于评论不再公开，通知Webhook
+    if outcome.hidden {
+        match get_comment_by_id(pool.as_ref(), &report.comment_id).await {
+            Ok(Some(comment)) => {
+                if let Some(event) = webhook::status_event(STATUS_APPROVED, &comment.status)
+                    && let Err(e) = webhook::dispatch(pool.as_ref(), event, &comment).await
+                {
+                    eprintln!("Failed to dispatch webhook: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Error getting reported comment: {:?}", e),
+        }
+    }
+
+    // 第一条举报时通知，应用设置了通知邮箱时通知该邮箱，否则通知管理员
+    if outcome.inserted && outcome.report_count == 1 {
+        match get_comment_by_id(pool.as_ref(), &report.comment_id).await {
+            Ok(Some(comment)) => {
+                let subject = format!("评论被举报: {} - {}", comment.app, comment.topic);
+                let content = format!(
+                    "评论 {} 被举报\n举报理由：{}\n\n{}：\n{}",
+                    comment.id,
+                    report.reason,
+                    comment.user,
+                    comment.content
+                );
+                let result = match &app.notify_email {
+                    Some(notify_email) => {
+                        send_email(
+                            pool.as_ref(),
+                            EmailConfig::new(Some(subject), content, Some(notify_email.clone()))
+                                .with_template("notification"),
+                        )
+                        .await
+                    }
+                    None => {
+                        notify(pool.as_ref(), NotificationEvent::CommentReported, subject, content)
+                            .await
+                    }
+                };
+                if let Err(e) = result {
+                    eprintln!("Failed to send report notification: {:?}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("Error getting reported comment: {:?}", e),
+        }
+    }
+    Json(ApiResponse::<()>::message_success("感谢举报，我们会尽快处理".to_string())).into_response()
+}
+
+// 批量统计评论数请求体
+#[derive(Debug, serde::Deserialize)]
+pub struct CommentCountsBody {
+    app: String,         // 应用ID
+    key: String,         // 应用密钥
+    topics: Vec<String>, // 话题列表
+    tz: Option<String>,  // 显示时间使用的时区，默认为应用时区
+}
+
+// 单次最多统计的话题数
+const MAX_COUNT_TOPICS: usize = 100;
+
+// 批量获取话题评论数的处理函数，按请求顺序返回每个话题的评论数和最新评论时间
+pub async fn get_comment_counts(
+    State(pool): State<Arc<SqlitePool>>,
+    AxumJson(body): AxumJson<CommentCountsBody>,
+) -> impl IntoResponse {
+    if body.topics.is_empty() || body.topics.len() > MAX_COUNT_TOPICS {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!(
+                "topics must contain 1 to {} topics",
+                MAX_COUNT_TOPICS
+            ))),
+        )
+            .into_response();
+    }
+
+    if let Some(tz) = body.tz.as_deref().filter(|tz| !tz.is_empty())
+        && parse_timezone(tz).is_none()
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("unknown timezone: {}", tz))),
+        )
+            .into_response();
+    }
+
+    // 验证app和key
+    let app = match get_app_by_key(pool.as_ref(), &body.app, &body.key).await {
+        Ok(Some(app)) => app,
+        Ok(None) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate app key".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+    let time_format = TimeFormat::new(
+        body.tz
+            .as_deref()
+            .and_then(parse_timezone)
+            .unwrap_or_else(|| app.time_zone()),
+        false,
+        0,
+    );
+
+    match count_comments_by_topics(pool.as_ref(), &body.app, &body.topics).await {
+        Ok(counts) => {
+            let counts: HashMap<String, (i64, i64)> = counts
+                .into_iter()
+                .map(|c| (c.topic, (c.count, c.latest_time)))
+                .collect();
+            // 没有评论的话题返回0
+            let response: Vec<TopicCountResponse> = body
+                .topics
+                .iter()
+                .map(|topic| {
+                    let count = counts.get(topic);
+                    TopicCountResponse {
+                        topic: topic.clone(),
+                        count: count.map(|(count, _)| *count).unwrap_or(0),
+                        latest_time: count.map(|(_, latest)| time_format.format(*latest)),
+                        latest_time_iso: count.map(|(_, latest)| time_format.iso(*latest)),
+                        latest_timestamp: count.map(|(_, latest)| *latest),
+                    }
+                })
+                .collect();
+            Json(ApiResponse::data_success(response)).into_response()
+        }
+        Err(e) => {
+            eprintln!("Error counting comments: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to count comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 作者修改/删除评论请求体
+#[derive(Debug, serde::Deserialize)]
+pub struct AuthorCommentBody {
+    app: String, // 应用ID
+    key: String, // 应用密钥
+    #[serde(rename = "editToken")]
+    edit_token: String, // 发表评论时返回的编辑凭证
+    content: Option<String>, // 修改后的内容，删除时不需要
+}
+
+// 作者凭证校验结果
+enum AuthorCheck {
+    Allowed(Box<CommentApp>, Box<Comment>),
+    Denied(Response),
+}
+
+// 发表后允许作者修改/删除的时间（分钟），环境变量 `COMMENT_EDIT_WINDOW_MINUTES`，默认15
+fn edit_window_minutes() -> i64 {
+    env::var("COMMENT_EDIT_WINDOW_MINUTES")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(15)
+}
+
+// 校验app、评论和编辑凭证，以及是否仍在可编辑时间内
+async fn check_author(pool: &SqlitePool, id: &str, body: &AuthorCommentBody) -> AuthorCheck {
+    let app = match get_app_by_key(pool, &body.app, &body.key).await {
+        Ok(Some(app)) => app,
+        Ok(None) => {
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::UNAUTHORIZED,
+                    Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+                )
+                    .into_response(),
+            );
+        }
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to validate app key".to_string(),
+                    )),
+                )
+                    .into_response(),
+            );
+        }
+    };
+
+    // 已删除、已拒绝或垃圾评论视为不存在
+    let comment = match get_comment_by_id(pool, id).await {
+        Ok(Some(comment))
+            if comment.app == body.app
+                && (comment.status == STATUS_APPROVED || comment.status == STATUS_PENDING) =>
+        {
+            comment
+        }
+        Ok(_) => {
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiResponse::<()>::error("评论不存在".to_string())),
+                )
+                    .into_response(),
+            );
+        }
+        Err(e) => {
+            eprintln!("Error getting comment: {:?}", e);
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error("Failed to get comment".to_string())),
+                )
+                    .into_response(),
+            );
+        }
+    };
+
+    match get_edit_token_hash(pool, id).await {
+        Ok(Some(hash)) if hash == token::hash_token(&body.edit_token) => {}
+        Ok(_) => {
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::FORBIDDEN,
+                    Json(ApiResponse::<()>::error("编辑凭证无效".to_string())),
+                )
+                    .into_response(),
+            );
+        }
+        Err(e) => {
+            eprintln!("Error getting edit token: {:?}", e);
+            return AuthorCheck::Denied(
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error("Failed to get comment".to_string())),
+                )
+                    .into_response(),
+            );
+        }
+    }
+
+    if Utc::now().timestamp() - comment.create_time > edit_window_minutes() * 60 {
+        return AuthorCheck::Denied(
+            (
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error("已超过可编辑时间".to_string())),
+            )
+                .into_response(),
+        );
+    }
+
+    AuthorCheck::Allowed(Box::new(app), Box::new(comment))
+}
+
+// 作者修改评论的处理函数
+pub async fn edit_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    AxumJson(body): AxumJson<AuthorCommentBody>,
+) -> impl IntoResponse {
+    let (app, comment) = match check_author(pool.as_ref(), &id, &body).await {
+        AuthorCheck::Allowed(app, comment) => (app, comment),
+        AuthorCheck::Denied(response) => return response,
+    };
+
+    if let Some(message) = content_error(body.content.as_deref(), app.max_length) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    // 修改后的内容同样经过垃圾评论检测；先审后发的应用，修改后的评论需要重新审核
+    let content = body.content.as_deref().unwrap_or_default();
+    let edited = Comment {
+        content: content.to_string(),
+        ..Comment::clone(&comment)
+    };
+    let status = if !comment.is_author && is_spam_comment(pool.as_ref(), &edited, None).await {
+        STATUS_SPAM
+    } else if app.moderation == MODERATION_PRE {
+        STATUS_PENDING
+    } else {
+        comment.status.as_str()
+    };
+
+    match update_comment_content(pool.as_ref(), &comment, content, status, Utc::now().timestamp())
+        .await
+    {
+        Ok(()) => match get_comment_by_id(pool.as_ref(), &id).await {
+            Ok(Some(updated)) => {
+                notify_edit(pool.as_ref(), &app, &comment, &updated).await;
+                let time_format = TimeFormat::new(app.time_zone(), false, 0);
+                let mut response = convert_to_response(&updated, &time_format);
+                render_markdown(std::slice::from_mut(&mut response), &app);
+                Json(ApiResponse::data_success(response)).into_response()
+            }
+            Ok(None) => (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("评论不存在".to_string())),
+            )
+                .into_response(),
+            Err(e) => {
+                eprintln!("Error getting comment: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error("Failed to get comment".to_string())),
+                )
+                    .into_response()
+            }
+        },
+        Err(e) => {
+            eprintln!("Error updating comment: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 评论修改后的通知：已公开的评论重新进入待审核时通知管理员，状态变化时通知Webhook
+async fn notify_edit(pool: &SqlitePool, app: &CommentApp, previous: &Comment, updated: &Comment) {
+    if previous.status == STATUS_APPROVED && updated.status == STATUS_PENDING {
+        let topic = get_topic(pool, &updated.app, &updated.topic)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting comment topic: {:?}", e);
+                None
+            });
+        let (title, url) = topic_link(&updated.topic, topic.as_ref());
+        let subject = format!("待审核评论（已修改）: {} - {}", updated.app, title);
+        let content = format!(
+            "评论已修改: {} - {}\n{}\n修改前：\n{}\n\n{}",
+            updated.app,
+            title,
+            url,
+            previous.content,
+            serde_json::to_string_pretty(updated).unwrap_or_default()
+        );
+        if let Err(e) = notify_comment(pool, app, subject, content).await {
+            eprintln!("Failed to send email: {:?}", e);
+        }
+    }
+    if let Some(event) = webhook::status_event(&previous.status, &updated.status)
+        && let Err(e) = webhook::dispatch(pool, event, updated).await
+    {
+        eprintln!("Failed to dispatch webhook: {:?}", e);
+    }
+}
+
+// 作者删除评论的处理函数，评论标记为已删除而不物理删除
+pub async fn delete_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    AxumJson(body): AxumJson<AuthorCommentBody>,
+) -> impl IntoResponse {
+    let mut comment = match check_author(pool.as_ref(), &id, &body).await {
+        AuthorCheck::Allowed(_, comment) => comment,
+        AuthorCheck::Denied(response) => return response,
+    };
+
+    match update_comments_status(pool.as_ref(), &[id], STATUS_DELETED).await {
+        Ok(rows_affected) => {
+            if let Some(event) = webhook::status_event(&comment.status, STATUS_DELETED) {
+                comment.status = STATUS_DELETED.to_string();
+                if let Err(e) = webhook::dispatch(pool.as_ref(), event, &comment).await {
+                    eprintln!("Failed to dispatch webhook: {:?}", e);
+                }
+            }
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Err(e) => {
+            eprintln!("Error deleting comment: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to delete comment".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::session::{CommentSession, sign_session};
+
+    fn response(id: &str, parent_id: Option<&str>) -> CommentResponse {
+        CommentResponse {
+            id: id.to_string(),
+            content: String::new(),
+            user: String::new(),
+            like: 0,
+            create_time: String::new(),
+            create_time_iso: String::new(),
+            create_timestamp: 0,
+            to: None,
+            parent_id: parent_id.map(|p| p.to_string()),
+            replies: None,
+            liked_by_me: None,
+            edited: false,
+            pinned: false,
+            author: false,
+            verified: false,
+            deleted: false,
+            content_html: None,
+            reactions: Vec::new(),
+            my_reactions: None,
+        }
+    }
+
+    fn placeholder(id: &str, parent_id: Option<&str>) -> CommentResponse {
+        CommentResponse {
+            deleted: true,
+            ..response(id, parent_id)
+        }
+    }
+
+    fn ids(comments: &[CommentResponse]) -> Vec<&str> {
+        comments.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_client_ip_trusts_only_configured_header() {
+        let addr: SocketAddr = "192.168.1.5:40000".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "1.2.3.4, 10.0.0.1".parse().unwrap());
+        headers.insert("x-real-ip", "10.0.0.2".parse().unwrap());
+
+        // 未配置时忽略客户端可伪造的代理头
+        assert_eq!(client_ip(&headers, &addr, None), "192.168.1.5");
+        assert_eq!(client_ip(&headers, &addr, Some("x-real-ip")), "10.0.0.2");
+        // 多个值时取代理追加的最后一个，客户端自带的值不可信
+        assert_eq!(client_ip(&headers, &addr, Some("X-Forwarded-For")), "10.0.0.1");
+        assert_eq!(client_ip(&headers, &addr, Some("cf-connecting-ip")), "192.168.1.5");
+    }
+
+    #[test]
+    fn test_visitor_key_prefers_session_user() {
+        let addr: SocketAddr = "10.0.0.1:40000".parse().unwrap();
+        let headers = HeaderMap::new();
+        let key = visitor_key("blog", None, &headers, &addr);
+        assert!(key.starts_with("ip:"));
+        assert!(!key.contains("10.0.0.1"));
+        // 不依赖服务端密钥，重启后保持不变
+        assert_eq!(
+            key,
+            format!("ip:{}", token::hash_token("visitor-ip:10.0.0.1"))
+        );
+        assert_eq!(
+            key,
+            visitor_key("blog", None, &headers, &"10.0.0.1:50000".parse().unwrap())
+        );
+
+        let session = sign_session(&CommentSession {
+            app: "blog".to_string(),
+            email: "alice@example.com".to_string(),
+            expire_time: Utc::now().timestamp() + 600,
+        });
+        assert_eq!(
+            visitor_key("blog", Some(&session), &headers, &addr),
+            "u:alice@example.com"
+        );
+        // 其他应用的会话和无效会话按IP识别
+        assert_eq!(visitor_key("docs", Some(&session), &headers, &addr), key);
+        assert_eq!(
+            visitor_key("blog", Some("alice@example.com"), &headers, &addr),
+            key
+        );
+    }
+
+    #[test]
+    fn test_build_comment_tree_nests_replies() {
+        let roots = vec![response("r2", None), response("r1", None)];
+        let replies = vec![response("c1", Some("r1")), response("c2", Some("r1"))];
+        let tree = build_comment_tree(roots, replies, 3);
+        assert_eq!(ids(&tree), vec!["r2", "r1"]);
+        assert!(tree[0].replies.is_none());
+        assert_eq!(ids(tree[1].replies.as_ref().unwrap()), vec!["c1", "c2"]);
+    }
+
+    #[test]
+    fn test_build_comment_tree_flattens_beyond_depth() {
+        let roots = vec![response("root", None)];
+        let replies = vec![
+            response("d1", Some("root")),
+            response("d2", Some("d1")),
+            response("d3", Some("d2")),
+        ];
+        let tree = build_comment_tree(roots, replies, 1);
+        assert_eq!(ids(&tree), vec!["root"]);
+        let replies = tree[0].replies.as_ref().unwrap();
+        assert_eq!(ids(replies), vec!["d1", "d2", "d3"]);
+        assert!(replies.iter().all(|r| r.replies.is_none()));
+    }
+
+    #[test]
+    fn test_build_comment_tree_keeps_replies_of_deleted_comments() {
+        let roots = vec![placeholder("r1", None), placeholder("r2", None), response("r3", None)];
+        let replies = vec![
+            placeholder("d1", Some("r1")),
+            response("c1", Some("d1")),
+            placeholder("d2", Some("r2")),
+            placeholder("d3", Some("r3")),
+        ];
+        let tree = build_comment_tree(roots, replies, 3);
+
+        // 已删除的评论下仍有公开回复时保留占位，没有时移除
+        assert_eq!(ids(&tree), vec!["r1", "r3"]);
+        assert!(tree[0].deleted);
+        let d1 = &tree[0].replies.as_ref().unwrap()[0];
+        assert!(d1.deleted);
+        assert_eq!(ids(d1.replies.as_ref().unwrap()), vec!["c1"]);
+        assert!(tree[1].replies.is_none());
+
+        // 平铺时去掉占位节点
+        let roots = vec![response("root", None)];
+        let replies = vec![placeholder("d1", Some("root")), response("c1", Some("d1"))];
+        let tree = build_comment_tree(roots, replies, 1);
+        assert_eq!(ids(tree[0].replies.as_ref().unwrap()), vec!["c1"]);
+    }
+
+    #[test]
+    fn test_attach_reactions_follows_app_order() {
+        let count = |id: &str, reaction: &str, count: i64| ReactionCount {
+            comment_id: id.to_string(),
+            reaction: reaction.to_string(),
+            count,
+        };
+        let counts = vec![count("c1", "🎉", 1), count("c1", "👍", 3), count("c1", "🙈", 2)];
+        let mine = vec![("c1".to_string(), "🎉".to_string())];
+        let reaction_set = vec!["👍".to_string(), "🎉".to_string()];
+        let mut comments = vec![response("c1", None), response("c2", None)];
+        attach_reactions(&mut comments, &counts, Some(&mine), &reaction_set);
+
+        let summary: Vec<(&str, i64)> = comments[0]
+            .reactions
+            .iter()
+            .map(|r| (r.reaction.as_str(), r.count))
+            .collect();
+        assert_eq!(summary, vec![("👍", 3), ("🎉", 1)]);
+        assert_eq!(comments[0].my_reactions, Some(vec!["🎉".to_string()]));
+        assert!(comments[1].reactions.is_empty());
+        assert_eq!(comments[1].my_reactions, Some(Vec::new()));
+    }
+
+    #[test]
+    fn test_is_owner_email() {
+        let owners = "owner@example.com, Admin@Example.com";
+        assert!(is_owner_email(owners, "owner@example.com"));
+        assert!(is_owner_email(owners, "admin@example.com"));
+        assert!(!is_owner_email(owners, "alice@example.com"));
+        assert!(!is_owner_email("", ""));
+    }
+
+    // 按分页参数加载示例话题的评论，返回(评论id, 是否置顶)和下一页游标
+    async fn load_page(
+        pool: &SqlitePool,
+        app: &CommentApp,
+        cursor: Option<&str>,
+    ) -> (Vec<(String, bool)>, Option<String>) {
+        let query: CommentQuery = serde_json::from_value(serde_json::json!({
+            "a": "blog",
+            "k": "",
+            "t": "/posts/a",
+            "limit": 2,
+            "cursor": cursor,
+        }))
+        .unwrap();
+        let cursor = cursor.and_then(CommentCursor::decode);
+        let response = load_comments(pool, app, &query, "", CommentSort::Newest, cursor)
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let page = &body["payload"];
+        let comments = page["comments"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["_id"].as_str().unwrap().to_string(),
+                    c["pinned"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        (comments, page["nextCursor"].as_str().map(str::to_string))
+    }
+
+    #[tokio::test]
+    async fn test_pinned_comments_lead_first_page_only() {
+        let pool = crate::dao::database::init_test_pool().await;
+        sqlx::query("INSERT INTO comment_apps (id, key) VALUES ('blog', '')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let app = crate::dao::comment_app::get_comment_app(&pool, "blog")
+            .await
+            .unwrap()
+            .unwrap();
+        for (id, create_time, pinned_time) in [
+            ("c1", 1, None),
+            ("c2", 2, None),
+            ("c3", 3, None),
+            ("p1", 4, Some(10)),
+            ("p2", 5, Some(20)),
+        ] {
+            let comment = Comment {
+                id: id.to_string(),
+                app: "blog".to_string(),
+                topic: "/posts/a".to_string(),
+                content: id.to_string(),
+                create_time,
+                user: "alice".to_string(),
+                like: 0,
+                to_user: None,
+                to_content: None,
+                parent_id: None,
+                status: STATUS_APPROVED.to_string(),
+                edit_time: None,
+                pinned_time: None,
+                is_author: false,
+                verified: false,
+            };
+            insert_comment(&pool, &comment, None).await.unwrap();
+            if pinned_time.is_some() {
+                crate::dao::comment::set_comment_pinned(&pool, id, pinned_time)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        // 置顶评论按置顶时间倒序排在第一页最前，不占limit，也不重复出现在普通列表中
+        let (first, cursor) = load_page(&pool, &app, None).await;
+        let pinned = |id: &str, pinned: bool| (id.to_string(), pinned);
+        assert_eq!(
+            first,
+            vec![
+                pinned("p2", true),
+                pinned("p1", true),
+                pinned("c3", false),
+                pinned("c2", false)
+            ]
+        );
+
+        // 后续页不再返回置顶评论
+        let (second, cursor) = load_page(&pool, &app, cursor.as_deref()).await;
+        assert_eq!(second, vec![pinned("c1", false)]);
+        assert!(cursor.is_none());
+    }
+}
@@ -0,0 +1,607 @@
+use axum::{
+    extract::{Json as AxumJson, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use regex::Regex;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::{
+    AdminKeyQuery, ApiResponse, PageResponse, admin_forbidden, default_page, default_size,
+    is_admin_key,
+};
+use crate::dao::blocklist::{delete_block_rule, get_block_rules, insert_block_rule};
+use crate::dao::comment::{
+    CommentFilter, CommentSearch, STATUS_APPROVED, STATUS_PENDING, STATUS_REJECTED, STATUS_SPAM,
+    full_text_search, get_comment_by_id, get_comment_edits, moderate_comments, search_comments,
+    set_comment_pinned,
+};
+use crate::dao::report::{get_pending_reports, list_reported_comments, resolve_reports};
+use crate::util::highlight;
+use crate::util::notification::notify_reply;
+use crate::util::webhook::{self, status_event};
+
+// 管理员评论列表查询参数
+#[derive(Deserialize)]
+pub struct AdminCommentQuery {
+    key: String,
+    app: Option<String>,
+    topic: Option<String>,
+    status: Option<String>, // 默认只列出待审核评论，传all列出全部
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 管理员全文搜索查询参数
+#[derive(Deserialize)]
+pub struct SearchCommentQuery {
+    key: String,
+    q: String,
+    app: Option<String>,
+    topic: Option<String>,
+    status: Option<String>,
+    from: Option<i64>, // 起始时间（秒）
+    to: Option<i64>,   // 结束时间（秒）
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 批量审核请求体
+#[derive(Deserialize)]
+pub struct BulkModerateRequest {
+    ids: Vec<String>,
+    action: String, // approve、reject、spam
+}
+
+// 举报队列查询参数
+#[derive(Deserialize)]
+pub struct ReportQueueQuery {
+    key: String,
+    app: Option<String>,
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 处理举报请求体
+#[derive(Deserialize)]
+pub struct ResolveReportsRequest {
+    action: String, // dismiss：驳回举报并恢复被隐藏的评论；remove：拒绝评论
+}
+
+// 添加屏蔽规则请求体
+#[derive(Deserialize)]
+pub struct BlockRuleRequest {
+    pattern: String,
+    #[serde(rename = "isRegex", default)]
+    is_regex: bool,
+}
+
+// 审核操作对应的评论状态
+fn action_status(action: &str) -> Option<&'static str> {
+    match action {
+        "approve" => Some(STATUS_APPROVED),
+        "reject" => Some(STATUS_REJECTED),
+        "spam" => Some(STATUS_SPAM),
+        _ => None,
+    }
+}
+
+// 查询评论审核队列的处理函数
+pub async fn get_comments(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminCommentQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    let status = match query.status.as_deref() {
+        None | Some("") => Some(STATUS_PENDING.to_string()),
+        Some("all") => None,
+        Some(status) => Some(status.to_string()),
+    };
+    let filter = CommentFilter {
+        app: query.app,
+        topic: query.topic,
+        status,
+    };
+
+    match search_comments(pool.as_ref(), &filter, page, size).await {
+        Ok((items, total)) => Json(ApiResponse::data_success(PageResponse {
+            total,
+            page,
+            size,
+            items,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error searching comments: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 全文搜索评论的处理函数，snippet为转义后的HTML，命中部分用<mark>标记
+pub async fn search(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<SearchCommentQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let q = query.q.trim().to_string();
+    if q.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("q required".to_string())),
+        )
+            .into_response();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    let search = CommentSearch {
+        query: q.clone(),
+        app: query.app.filter(|s| !s.is_empty()),
+        topic: query.topic.filter(|s| !s.is_empty()),
+        status: query.status.filter(|s| !s.is_empty() && s != "all"),
+        from: query.from,
+        to: query.to,
+    };
+
+    match full_text_search(pool.as_ref(), &search, page, size).await {
+        Ok((mut items, total)) => {
+            for hit in &mut items {
+                // 短关键词没有FTS片段，在内容中截取
+                if hit.snippet.is_empty() {
+                    hit.snippet = highlight::excerpt(&hit.comment.content, &q, 24);
+                }
+                hit.snippet = highlight::to_html(&hit.snippet);
+            }
+            Json(ApiResponse::data_success(PageResponse {
+                total,
+                page,
+                size,
+                items,
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            eprintln!("Error searching comments: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to search comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 更新评论状态，待审核的回复审核通过后通知被回复的评论作者，状态变化时通知Webhook
+//
+// 作者已删除和被举报隐藏的评论不受审核操作影响。
+async fn moderate(pool: &SqlitePool, ids: &[String], status: &str) -> Response {
+    match moderate_comments(pool, ids, status).await {
+        Ok(previous) if !previous.is_empty() => {
+            let changed = previous.len();
+            for mut comment in previous {
+                let event = status_event(&comment.status, status);
+                let approved_reply = status == STATUS_APPROVED
+                    && comment.status == STATUS_PENDING
+                    && comment.parent_id.is_some();
+                comment.status = status.to_string();
+                if approved_reply && let Err(e) = notify_reply(pool, &comment).await {
+                    eprintln!("Failed to send reply notification: {:?}", e);
+                }
+                if let Some(event) = event
+                    && let Err(e) = webhook::dispatch(pool, event, &comment).await
+                {
+                    eprintln!("Failed to dispatch webhook: {:?}", e);
+                }
+            }
+            Json(ApiResponse::data_success(changed)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error updating comment status: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment status".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 审核通过评论的处理函数
+pub async fn approve_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    moderate(pool.as_ref(), &[id], STATUS_APPROVED).await
+}
+
+// 拒绝评论的处理函数
+pub async fn reject_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    moderate(pool.as_ref(), &[id], STATUS_REJECTED).await
+}
+
+// 置顶或取消置顶评论
+async fn set_pinned(pool: &SqlitePool, id: &str, pinned_time: Option<i64>) -> Response {
+    match set_comment_pinned(pool, id, pinned_time).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到已公开的评论".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error updating comment pinned: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment pinned".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 置顶评论的处理函数，多条置顶评论按置顶时间倒序
+pub async fn pin_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    set_pinned(pool.as_ref(), &id, Some(Utc::now().timestamp())).await
+}
+
+// 取消置顶评论的处理函数
+pub async fn unpin_comment(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    set_pinned(pool.as_ref(), &id, None).await
+}
+
+// 查询举报队列的处理函数，返回有待处理举报的评论及举报明细
+pub async fn get_reports(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<ReportQueueQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    let (mut items, total) =
+        match list_reported_comments(pool.as_ref(), query.app.as_deref(), page, size).await {
+            Ok(result) => result,
+            Err(e) => {
+                eprintln!("Error getting reported comments: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to get reported comments".to_string(),
+                    )),
+                )
+                    .into_response();
+            }
+        };
+
+    let ids: Vec<String> = items.iter().map(|item| item.comment.id.clone()).collect();
+    match get_pending_reports(pool.as_ref(), &ids).await {
+        Ok(reports) => {
+            for report in reports {
+                if let Some(item) = items.iter_mut().find(|item| item.comment.id == report.comment_id)
+                {
+                    item.reports.push(report);
+                }
+            }
+            Json(ApiResponse::data_success(PageResponse {
+                total,
+                page,
+                size,
+                items,
+            }))
+            .into_response()
+        }
+        Err(e) => {
+            eprintln!("Error getting comment reports: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get reported comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 处理评论举报的处理函数
+pub async fn resolve_comment_reports(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<ResolveReportsRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let status = match body.action.as_str() {
+        "dismiss" => STATUS_APPROVED,
+        "remove" => STATUS_REJECTED,
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(
+                    "action must be dismiss or remove".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    let previous = get_comment_by_id(pool.as_ref(), &id).await.unwrap_or_else(|e| {
+        eprintln!("Error getting comment: {:?}", e);
+        None
+    });
+    match resolve_reports(pool.as_ref(), &id, status, Utc::now().timestamp()).await {
+        Ok(resolved) if resolved > 0 => {
+            // 处理举报改变了评论的公开状态时通知Webhook
+            if let Some(previous) = previous {
+                match get_comment_by_id(pool.as_ref(), &id).await {
+                    Ok(Some(comment)) => {
+                        if let Some(event) = status_event(&previous.status, &comment.status)
+                            && let Err(e) = webhook::dispatch(pool.as_ref(), event, &comment).await
+                        {
+                            eprintln!("Failed to dispatch webhook: {:?}", e);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Error getting comment: {:?}", e),
+                }
+            }
+            Json(ApiResponse::data_success(resolved)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("没有待处理的举报".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error resolving comment reports: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to resolve comment reports".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 查询评论编辑历史的处理函数
+pub async fn get_edit_history(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match get_comment_edits(pool.as_ref(), &id).await {
+        Ok(edits) => Json(ApiResponse::data_success(edits)).into_response(),
+        Err(e) => {
+            eprintln!("Error getting comment edits: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment edits".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 批量审核评论的处理函数
+pub async fn bulk_moderate(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<BulkModerateRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let Some(status) = action_status(&body.action) else {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "action must be approve, reject or spam".to_string(),
+            )),
+        )
+            .into_response();
+    };
+    if body.ids.is_empty() || body.ids.len() > 500 {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "ids must contain 1 to 500 comment ids".to_string(),
+            )),
+        )
+            .into_response();
+    }
+
+    moderate(pool.as_ref(), &body.ids, status).await
+}
+
+// 查询屏蔽规则列表的处理函数
+pub async fn get_blocklist(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match get_block_rules(pool.as_ref()).await {
+        Ok(rules) => Json(ApiResponse::data_success(rules)).into_response(),
+        Err(e) => {
+            eprintln!("Error getting block rules: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get block rules".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 添加屏蔽规则的处理函数
+pub async fn add_block_rule(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<BlockRuleRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let pattern = body.pattern.trim();
+    if pattern.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("pattern required".to_string())),
+        )
+            .into_response();
+    }
+    // 正则规则在保存前校验语法
+    if body.is_regex
+        && let Err(e) = Regex::new(pattern)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("invalid regex: {}", e))),
+        )
+            .into_response();
+    }
+
+    match insert_block_rule(pool.as_ref(), pattern, body.is_regex, Utc::now().timestamp()).await {
+        Ok(id) => Json(ApiResponse::data_success(id)).into_response(),
+        Err(e) => {
+            eprintln!("Error inserting block rule: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to add block rule".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 删除屏蔽规则的处理函数
+pub async fn remove_block_rule(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<i64>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match delete_block_rule(pool.as_ref(), id).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error deleting block rule: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to delete block rule".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use axum::{
+    extract::{Json as AxumJson, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use sqlx::types::Json as SqlxJson;
+use std::env;
+use std::sync::Arc;
+
+use super::{AdminKeyQuery, ApiResponse, admin_forbidden, is_admin_key};
+use crate::dao::comment_app::{
+    CommentApp, CommentAppSettings, MODERATION_POST, MODERATION_PRE, get_comment_app,
+    get_comment_apps, insert_comment_app, rotate_comment_app_key, set_feed_token_hash,
+    update_comment_app_settings,
+};
+use crate::util::email::validate_address;
+use crate::util::time::parse_timezone;
+use crate::util::token::{hash_token, random_token};
+
+// 评论最大长度上限（字节）
+const MAX_CONTENT_LENGTH: i64 = 65535;
+
+// 表情回应数量和单个表情长度（字符）上限，带肤色和ZWJ连接的组合表情最长约10个字符
+const MAX_REACTIONS: usize = 20;
+const MAX_REACTION_CHARS: usize = 16;
+
+// 创建评论应用请求体
+#[derive(Deserialize)]
+pub struct CreateCommentAppRequest {
+    id: String,
+    moderation: Option<String>,
+    #[serde(rename = "maxLength")]
+    max_length: Option<i64>,
+    #[serde(rename = "notifyEmail")]
+    notify_email: Option<String>,
+    #[serde(default)]
+    markdown: bool,
+    #[serde(rename = "allowImages", default)]
+    allow_images: bool,
+    reactions: Option<Vec<String>>, // 不传时使用默认表情
+    #[serde(rename = "verifyIdentity", default)]
+    verify_identity: bool,
+    timezone: Option<String>, // IANA时区名，不传时使用默认时区
+}
+
+// 修改评论应用设置请求体，未传的字段保持不变
+#[derive(Deserialize)]
+pub struct UpdateCommentAppRequest {
+    moderation: Option<String>,
+    #[serde(rename = "maxLength")]
+    max_length: Option<i64>,
+    #[serde(rename = "notifyEmail")]
+    notify_email: Option<String>, // 传空字符串清除通知邮箱
+    disabled: Option<bool>,
+    markdown: Option<bool>,
+    #[serde(rename = "allowImages")]
+    allow_images: Option<bool>,
+    reactions: Option<Vec<String>>, // 传空数组恢复默认表情
+    #[serde(rename = "verifyIdentity")]
+    verify_identity: Option<bool>,
+    timezone: Option<String>, // 传空字符串恢复默认时区
+}
+
+// 轮换key查询参数
+#[derive(Deserialize)]
+pub struct RotateKeyQuery {
+    key: String,
+    #[serde(rename = "graceHours")]
+    grace_hours: Option<i64>, // 旧key宽限期（小时），默认读取 COMMENT_KEY_GRACE_HOURS
+}
+
+// 新key响应，key只在创建和轮换时返回一次
+#[derive(Serialize)]
+pub struct AppKeyResponse {
+    id: String,
+    key: String,
+    #[serde(rename = "oldKeyExpireTime", skip_serializing_if = "Option::is_none")]
+    old_key_expire_time: Option<i64>,
+}
+
+// 旧key默认宽限期（小时），环境变量 `COMMENT_KEY_GRACE_HOURS`，默认24
+fn default_grace_hours() -> i64 {
+    env::var("COMMENT_KEY_GRACE_HOURS")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .unwrap_or(24)
+}
+
+// 校验应用设置，不合法时返回错误信息
+fn settings_error(
+    moderation: Option<&str>,
+    max_length: Option<i64>,
+    notify_email: Option<&str>,
+    timezone: Option<&str>,
+) -> Option<String> {
+    if let Some(moderation) = moderation
+        && moderation != MODERATION_POST
+        && moderation != MODERATION_PRE
+    {
+        return Some("moderation must be post or pre".to_string());
+    }
+    if let Some(max_length) = max_length
+        && !(1..=MAX_CONTENT_LENGTH).contains(&max_length)
+    {
+        return Some(format!("maxLength must be 1 to {}", MAX_CONTENT_LENGTH));
+    }
+    if let Some(email) = notify_email.filter(|e| !e.is_empty())
+        && let Err(e) = validate_address(email)
+    {
+        return Some(e);
+    }
+    if let Some(timezone) = timezone.filter(|t| !t.is_empty())
+        && parse_timezone(timezone).is_none()
+    {
+        return Some(format!("unknown timezone: {}", timezone));
+    }
+    None
+}
+
+// 去掉表情两端的空白，校验和保存使用同一个值
+fn trim_reactions(reactions: Vec<String>) -> Vec<String> {
+    reactions
+        .into_iter()
+        .map(|r| r.trim().to_string())
+        .collect()
+}
+
+// 校验去掉空白后的表情回应设置，不合法时返回错误信息
+fn reactions_error(reactions: &[String]) -> Option<String> {
+    if reactions.len() > MAX_REACTIONS {
+        return Some(format!("reactions must be at most {} items", MAX_REACTIONS));
+    }
+    for (i, reaction) in reactions.iter().enumerate() {
+        if reaction.is_empty() || reaction.chars().count() > MAX_REACTION_CHARS {
+            return Some(format!(
+                "reaction must be 1 to {} characters",
+                MAX_REACTION_CHARS
+            ));
+        }
+        if reactions[..i].contains(reaction) {
+            return Some(format!("duplicate reaction: {}", reaction));
+        }
+    }
+    None
+}
+
+// 应用id只允许字母、数字、下划线和短横线
+fn is_valid_app_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+// 查询评论应用列表的处理函数
+pub async fn get_apps(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match get_comment_apps(pool.as_ref()).await {
+        Ok(apps) => Json(ApiResponse::data_success(apps)).into_response(),
+        Err(e) => {
+            eprintln!("Error getting comment apps: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment apps".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 创建评论应用的处理函数，返回生成的key
+pub async fn create_app(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<CreateCommentAppRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if !is_valid_app_id(&body.id) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "id must be 1 to 64 letters, digits, '_' or '-'".to_string(),
+            )),
+        )
+            .into_response();
+    }
+    if let Some(message) = settings_error(
+        body.moderation.as_deref(),
+        body.max_length,
+        body.notify_email.as_deref(),
+        body.timezone.as_deref(),
+    ) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+    let reactions = body.reactions.map(trim_reactions);
+    if let Some(message) = reactions.as_deref().and_then(reactions_error) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    match get_comment_app(pool.as_ref(), &body.id).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(ApiResponse::<()>::error("应用已存在".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error getting comment app: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to create comment app".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let key = random_token();
+    let app = CommentApp {
+        id: body.id,
+        moderation: body.moderation.unwrap_or(MODERATION_POST.to_string()),
+        max_length: body.max_length.unwrap_or(1023),
+        notify_email: body.notify_email.filter(|e| !e.is_empty()),
+        disabled: false,
+        markdown: body.markdown,
+        allow_images: body.allow_images,
+        reactions: reactions.filter(|r| !r.is_empty()).map(SqlxJson),
+        verify_identity: body.verify_identity,
+        timezone: body.timezone.filter(|t| !t.is_empty()),
+        create_time: Some(Utc::now().timestamp()),
+        key_hash: Some(hash_token(&key)),
+        old_key_hash: None,
+        old_key_expire_time: None,
+        feed_token_hash: None,
+    };
+
+    match insert_comment_app(pool.as_ref(), &app).await {
+        Ok(()) => Json(ApiResponse::data_success(AppKeyResponse {
+            id: app.id,
+            key,
+            old_key_expire_time: None,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error inserting comment app: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to create comment app".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 修改评论应用设置的处理函数，disabled为true时停用应用
+pub async fn update_app(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<UpdateCommentAppRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if let Some(message) = settings_error(
+        body.moderation.as_deref(),
+        body.max_length,
+        body.notify_email.as_deref(),
+        body.timezone.as_deref(),
+    ) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+    let reactions = body.reactions.map(trim_reactions);
+    if let Some(message) = reactions.as_deref().and_then(reactions_error) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    let settings = CommentAppSettings {
+        moderation: body.moderation,
+        max_length: body.max_length,
+        notify_email: body.notify_email.map(|e| Some(e).filter(|e| !e.is_empty())),
+        disabled: body.disabled,
+        markdown: body.markdown,
+        allow_images: body.allow_images,
+        reactions: reactions.map(|r| Some(r).filter(|r| !r.is_empty())),
+        verify_identity: body.verify_identity,
+        timezone: body.timezone.map(|t| Some(t).filter(|t| !t.is_empty())),
+    };
+
+    match update_comment_app_settings(pool.as_ref(), &id, &settings).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error updating comment app: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment app".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 轮换评论应用key的处理函数，旧key在宽限期内仍然有效
+pub async fn rotate_key(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<RotateKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let grace_hours = query.grace_hours.unwrap_or_else(default_grace_hours).max(0);
+    let old_key_expire_time = Utc::now().timestamp() + grace_hours * 3600;
+    let key = random_token();
+
+    match rotate_comment_app_key(pool.as_ref(), &id, &hash_token(&key), old_key_expire_time).await
+    {
+        Ok(rows_affected) if rows_affected > 0 => Json(ApiResponse::data_success(AppKeyResponse {
+            id,
+            key,
+            old_key_expire_time: Some(old_key_expire_time),
+        }))
+        .into_response(),
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error rotating comment app key: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to rotate comment app key".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 生成评论订阅凭证的处理函数，旧凭证立即失效，新凭证只返回一次
+pub async fn generate_feed_token(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let token = random_token();
+    match set_feed_token_hash(pool.as_ref(), &id, &hash_token(&token)).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(token)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error setting feed token: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to generate feed token".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reactions_error_accepts_multi_codepoint_emoji() {
+        let reactions: Vec<String> = ["👍", "👨‍👩‍👧", "🏳️‍🌈", "👩🏽‍🚀", "+1"]
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert!(reactions_error(&reactions).is_none());
+        assert!(reactions_error(&["".to_string()]).is_some());
+        assert!(reactions_error(&["a".repeat(17)]).is_some());
+        assert!(reactions_error(&["👍".to_string(), "👍".to_string()]).is_some());
+
+        // 先去掉空白再校验
+        let reactions = trim_reactions(vec![" 👍".to_string(), "👍 ".to_string()]);
+        assert_eq!(reactions, vec!["👍".to_string(), "👍".to_string()]);
+        assert!(reactions_error(&reactions).is_some());
+        assert!(reactions_error(&trim_reactions(vec!["  ".to_string()])).is_some());
+    }
+}
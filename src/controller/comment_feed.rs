@@ -0,0 +1,168 @@
+use axum::{
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::ApiResponse;
+use crate::dao::comment::get_latest_comments;
+use crate::dao::comment_app::get_comment_app;
+use crate::util::email::{api_base_url, hide_email};
+use crate::util::feed::{FeedEntry, build_atom_feed};
+use crate::dao::topic::get_topics_by_names;
+use crate::util::notification::topic_link;
+
+// 订阅默认和最大条数
+const DEFAULT_FEED_LIMIT: i64 = 50;
+const MAX_FEED_LIMIT: i64 = 100;
+
+// 评论订阅查询参数
+#[derive(Deserialize)]
+pub struct FeedQuery {
+    a: String,         // app
+    token: String,     // 只读订阅凭证
+    t: Option<String>, // 话题，不传时订阅整个应用
+    limit: Option<i64>,
+}
+
+// 订阅地址（不含凭证），作为feed的id
+fn feed_url(base_url: &str, app: &str, topic: Option<&str>) -> String {
+    let mut params = vec![("a", app)];
+    if let Some(topic) = topic {
+        params.push(("t", topic));
+    }
+    let url = format!("{}/comment/feed", base_url.trim_end_matches('/'));
+    reqwest::Url::parse_with_params(&url, &params)
+        .map(|u| u.to_string())
+        .unwrap_or(url)
+}
+
+// 评论条目的id，只由服务域名、应用和评论id组成，话题地址变化时保持不变
+fn entry_id(base_url: &str, app: &str, comment_id: &str) -> String {
+    let host = reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .unwrap_or("localhost".to_string());
+    format!("tag:{},{}:comment/{}", host, app, comment_id)
+}
+
+// 获取评论Atom订阅的处理函数
+pub async fn get_feed(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<FeedQuery>,
+) -> impl IntoResponse {
+    // 验证订阅凭证，写入用的key不能用于订阅
+    match get_comment_app(pool.as_ref(), &query.a).await {
+        Ok(Some(app)) if !app.disabled && app.accepts_feed_token(&query.token) => {}
+        Ok(_) => {
+            return (
+                StatusCode::UNAUTHORIZED,
+                Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error getting comment app: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate feed token".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let topic = query.t.as_deref().filter(|t| !t.is_empty());
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_FEED_LIMIT)
+        .clamp(1, MAX_FEED_LIMIT);
+
+    let comments = match get_latest_comments(pool.as_ref(), &query.a, topic, limit).await {
+        Ok(comments) => comments,
+        Err(e) => {
+            eprintln!("Error getting latest comments: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comments".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+
+    // 使用话题登记的标题和地址
+    let base_url = api_base_url();
+    let mut names: Vec<String> = comments.iter().map(|c| c.topic.clone()).collect();
+    names.sort();
+    names.dedup();
+    match get_topics_by_names(pool.as_ref(), &query.a, &names).await {
+        Ok(topics) => {
+            let topics: HashMap<&str, _> = topics.iter().map(|t| (t.topic.as_str(), t)).collect();
+            let entries: Vec<FeedEntry> = comments
+                .iter()
+                .map(|comment| {
+                    let author = hide_email(&comment.user);
+                    let (title, link) =
+                        topic_link(&comment.topic, topics.get(comment.topic.as_str()).copied());
+                    FeedEntry {
+                        id: entry_id(&base_url, &query.a, &comment.id),
+                        title: format!("{} 评论了 {}", author, title),
+                        link,
+                        author,
+                        content: comment.content.clone(),
+                        updated: comment.edit_time.unwrap_or(comment.create_time),
+                    }
+                })
+                .collect();
+
+            let title = match topic {
+                Some(topic) => {
+                    let (title, _) = topic_link(topic, topics.get(topic).copied());
+                    format!("{} - {} 的评论", query.a, title)
+                }
+                None => format!("{} 的评论", query.a),
+            };
+            let xml = build_atom_feed(&feed_url(&base_url, &query.a, topic), &title, &entries);
+            (
+                [(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+                xml,
+            )
+                .into_response()
+        }
+        Err(e) => {
+            eprintln!("Error getting comment topics: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_id_ignores_topic_link() {
+        assert_eq!(
+            entry_id("https://example.com/api/v1", "blog", "c1"),
+            "tag:example.com,blog:comment/c1"
+        );
+        assert_eq!(entry_id("not a url", "blog", "c1"), "tag:localhost,blog:comment/c1");
+        assert_eq!(
+            feed_url("https://example.com/api/v1/", "blog", Some("/posts/a")),
+            "https://example.com/api/v1/comment/feed?a=blog&t=%2Fposts%2Fa"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use axum::{
+    extract::{Json as AxumJson, State},
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::env;
+use std::sync::Arc;
+
+use super::ApiResponse;
+use crate::dao::comment_app::{CommentApp, get_app_by_key};
+use crate::dao::identity::{consume_login_token, count_recent_login_tokens, insert_login_token};
+use crate::dao::topic::get_topic;
+use crate::util::email::{EmailConfig, is_email, send_email};
+use crate::util::notification::topic_link;
+use crate::util::session::{CommentSession, sign_session};
+use crate::util::token;
+
+// 同一邮箱两次申请登录链接的最小间隔（秒）
+const LOGIN_REQUEST_INTERVAL: i64 = 60;
+
+// 申请邮箱登录链接请求体
+#[derive(Deserialize)]
+pub struct LoginRequestBody {
+    app: String,   // 应用ID
+    key: String,   // 应用密钥
+    email: String, // 评论者邮箱
+    topic: String, // 所在话题，登录链接指向话题页面
+}
+
+// 验证登录凭证请求体
+#[derive(Deserialize)]
+pub struct VerifyBody {
+    app: String,
+    key: String,
+    token: String, // 登录链接中的commentToken参数
+}
+
+// 验证成功返回的会话
+#[derive(Serialize)]
+pub struct VerifyResponse {
+    email: String,
+    session: String, // 发表评论时传入的会话令牌
+    #[serde(rename = "expireTime")]
+    expire_time: i64,
+}
+
+// 登录链接有效期（分钟）
+fn login_token_minutes() -> i64 {
+    env::var("COMMENT_LOGIN_TOKEN_MINUTES")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(15)
+}
+
+// 会话有效期（天）
+fn session_days() -> i64 {
+    env::var("COMMENT_SESSION_DAYS")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|d| *d > 0)
+        .unwrap_or(30)
+}
+
+// 在话题页面地址上附加登录凭证参数
+fn login_link(page_url: &str, login_token: &str) -> String {
+    match reqwest::Url::parse(page_url) {
+        Ok(mut url) => {
+            url.query_pairs_mut().append_pair("commentToken", login_token);
+            url.to_string()
+        }
+        Err(_) => format!("{}?commentToken={}", page_url, login_token),
+    }
+}
+
+// 验证app和key，并要求应用开启了邮箱验证
+async fn verified_app(pool: &SqlitePool, app_id: &str, key: &str) -> Result<CommentApp, Response> {
+    match get_app_by_key(pool, app_id, key).await {
+        Ok(Some(app)) if app.verify_identity => Ok(app),
+        Ok(Some(_)) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("应用未开启邮箱验证".to_string())),
+        )
+            .into_response()),
+        Ok(None) => Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error("Unauthorized".to_string())),
+        )
+            .into_response()),
+        Err(e) => {
+            eprintln!("Error validating app key: {:?}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to validate app key".to_string(),
+                )),
+            )
+                .into_response())
+        }
+    }
+}
+
+// 申请邮箱登录链接的处理函数，链接发送到邮箱，指向所在话题的页面
+pub async fn request_login(
+    State(pool): State<Arc<SqlitePool>>,
+    AxumJson(body): AxumJson<LoginRequestBody>,
+) -> impl IntoResponse {
+    let app = match verified_app(pool.as_ref(), &body.app, &body.key).await {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let email = body.email.trim().to_lowercase();
+    if !is_email(&email) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("邮箱格式不正确".to_string())),
+        )
+            .into_response();
+    }
+    if body.topic.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("topic required".to_string())),
+        )
+            .into_response();
+    }
+
+    // 限制同一邮箱的申请频率
+    let now = Utc::now().timestamp();
+    match count_recent_login_tokens(pool.as_ref(), &app.id, &email, now - LOGIN_REQUEST_INTERVAL)
+        .await
+    {
+        Ok(0) => {}
+        Ok(_) => {
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ApiResponse::<()>::error("请求过于频繁，请稍后再试".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error counting login tokens: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to request login link".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    // 链接只指向话题登记的页面，不接受调用方传入的跳转地址
+    let topic = match get_topic(pool.as_ref(), &app.id, &body.topic).await {
+        Ok(topic) => topic,
+        Err(e) => {
+            eprintln!("Error getting comment topic: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment topic".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+    let (title, url) = topic_link(&body.topic, topic.as_ref());
+
+    // 生成登录凭证，只保存哈希
+    let login_token = token::random_token();
+    let minutes = login_token_minutes();
+    if let Err(e) = insert_login_token(
+        pool.as_ref(),
+        &token::hash_token(&login_token),
+        &app.id,
+        &email,
+        now,
+        now + minutes * 60,
+    )
+    .await
+    {
+        eprintln!("Error inserting login token: {:?}", e);
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiResponse::<()>::error(
+                "Failed to request login link".to_string(),
+            )),
+        )
+            .into_response();
+    }
+
+    let content = format!(
+        "点击以下链接验证邮箱后即可在「{}」发表评论，链接{}分钟内有效且只能使用一次：\n{}\n\n如果不是你本人操作，请忽略此邮件。",
+        title,
+        minutes,
+        login_link(&url, &login_token)
+    );
+    // 登录链接由收件人主动申请，退订过通知邮件的用户也能收到
+    match send_email(
+        pool.as_ref(),
+        EmailConfig::new(Some(format!("验证邮箱: {}", title)), content, Some(email))
+            .with_template("magic_link")
+            .transactional(),
+    )
+    .await
+    {
+        Ok(()) => Json(ApiResponse::<()>::message_success(
+            "登录链接已发送".to_string(),
+        ))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error sending login link: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to send login link".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 验证登录凭证的处理函数，凭证只能使用一次，成功后返回签名的会话令牌
+pub async fn verify_login(
+    State(pool): State<Arc<SqlitePool>>,
+    AxumJson(body): AxumJson<VerifyBody>,
+) -> impl IntoResponse {
+    let app = match verified_app(pool.as_ref(), &body.app, &body.key).await {
+        Ok(app) => app,
+        Err(response) => return response,
+    };
+
+    let now = Utc::now().timestamp();
+    match consume_login_token(pool.as_ref(), &token::hash_token(&body.token), &app.id, now).await
+    {
+        Ok(Some(email)) => {
+            let session = CommentSession {
+                app: app.id,
+                email,
+                expire_time: now + session_days() * 24 * 3600,
+            };
+            Json(ApiResponse::data_success(VerifyResponse {
+                session: sign_session(&session),
+                email: session.email,
+                expire_time: session.expire_time,
+            }))
+            .into_response()
+        }
+        Ok(None) => (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error("登录链接无效或已过期".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error consuming login token: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to verify login link".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_link() {
+        assert_eq!(
+            login_link("https://wycode.cn/posts/a?x=1", "abc"),
+            "https://wycode.cn/posts/a?x=1&commentToken=abc"
+        );
+        assert_eq!(
+            login_link("https://wycode.cn/posts/a#comments", "abc"),
+            "https://wycode.cn/posts/a?commentToken=abc#comments"
+        );
+    }
+}
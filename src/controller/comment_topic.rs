@@ -0,0 +1,189 @@
+use axum::{
+    extract::{Json as AxumJson, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+use super::{ApiResponse, PageResponse, admin_forbidden, default_page, default_size, is_admin_key};
+use crate::dao::topic::{TopicSettings, get_topic, list_topics, update_topic};
+
+// 话题标题最大长度（字符）
+const MAX_TITLE_LENGTH: usize = 200;
+
+// 话题列表查询参数
+#[derive(Deserialize)]
+pub struct TopicListQuery {
+    key: String,
+    app: String,
+    locked: Option<bool>, // 只列出锁定或未锁定的话题
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 修改话题查询参数，话题可能包含斜杠，因此不放在路径中
+#[derive(Deserialize)]
+pub struct TopicQuery {
+    key: String,
+    app: String,
+    topic: String,
+}
+
+// 修改话题请求体，未传的字段保持不变
+#[derive(Deserialize)]
+pub struct UpdateTopicRequest {
+    topic: Option<String>, // 新话题名，话题下的评论一起迁移
+    title: Option<String>, // 传空字符串清除标题
+    url: Option<String>,   // 传空字符串清除地址
+    locked: Option<bool>,
+}
+
+// 校验话题设置，不合法时返回错误信息
+fn topic_error(topic: Option<&str>, title: Option<&str>, url: Option<&str>) -> Option<String> {
+    if topic.is_some_and(|t| t.trim().is_empty()) {
+        return Some("topic must not be empty".to_string());
+    }
+    if let Some(title) = title
+        && title.chars().count() > MAX_TITLE_LENGTH
+    {
+        return Some(format!("title must be at most {} characters", MAX_TITLE_LENGTH));
+    }
+    if let Some(url) = url.filter(|u| !u.is_empty())
+        && !matches!(reqwest::Url::parse(url), Ok(u) if u.scheme() == "http" || u.scheme() == "https")
+    {
+        return Some("url must be an http or https URL".to_string());
+    }
+    None
+}
+
+// 查询应用下话题列表的处理函数
+pub async fn get_topics(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<TopicListQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    match list_topics(pool.as_ref(), &query.app, query.locked, page, size).await {
+        Ok((items, total)) => Json(ApiResponse::data_success(PageResponse {
+            total,
+            page,
+            size,
+            items,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error getting comment topics: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get comment topics".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 重命名话题，或修改话题标题、地址、锁定状态的处理函数
+pub async fn update(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<TopicQuery>,
+    AxumJson(body): AxumJson<UpdateTopicRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if let Some(message) = topic_error(
+        body.topic.as_deref(),
+        body.title.as_deref(),
+        body.url.as_deref(),
+    ) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    // 新话题名已登记时不能合并到该话题
+    let new_topic = body
+        .topic
+        .map(|t| t.trim().to_string())
+        .filter(|t| *t != query.topic);
+    if let Some(new_topic) = &new_topic {
+        match get_topic(pool.as_ref(), &query.app, new_topic).await {
+            Ok(None) => {}
+            Ok(Some(_)) => {
+                return (
+                    StatusCode::CONFLICT,
+                    Json(ApiResponse::<()>::error("话题已存在".to_string())),
+                )
+                    .into_response();
+            }
+            Err(e) => {
+                eprintln!("Error getting comment topic: {:?}", e);
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to update comment topic".to_string(),
+                    )),
+                )
+                    .into_response();
+            }
+        }
+    }
+
+    let settings = TopicSettings {
+        topic: new_topic,
+        title: body.title.map(|t| Some(t.trim().to_string()).filter(|t| !t.is_empty())),
+        url: body.url.map(|u| Some(u).filter(|u| !u.is_empty())),
+        locked: body.locked,
+    };
+
+    match update_topic(pool.as_ref(), &query.app, &query.topic, &settings).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error updating comment topic: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update comment topic".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topic_error() {
+        assert!(topic_error(None, Some("标题"), Some("https://wycode.cn/a")).is_none());
+        assert!(topic_error(None, None, Some("")).is_none());
+        assert!(topic_error(None, None, Some("javascript:alert(1)")).is_some());
+        assert!(topic_error(None, Some(&"长".repeat(201)), None).is_some());
+        assert!(topic_error(Some("/posts/new"), None, None).is_none());
+        assert!(topic_error(Some(" "), None, None).is_some());
+    }
+}
@@ -0,0 +1,331 @@
+use axum::{
+    extract::{Query, State},
+    http::{StatusCode, header},
+    response::{IntoResponse, Json},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{ApiResponse, admin_forbidden, is_admin_key};
+use crate::dao::comment::{
+    Comment, ImportOutcome, STATUS_APPROVED, STATUS_DELETED, STATUS_HIDDEN, STATUS_PENDING,
+    STATUS_REJECTED, STATUS_SPAM, get_app_comments, get_app_likes, import_comments,
+};
+use crate::dao::comment_app::get_comment_app;
+use crate::util::disqus::parse_disqus;
+
+// 导入请求体大小上限（字节）
+pub const MAX_IMPORT_BYTES: usize = 32 * 1024 * 1024;
+
+// 导出导入查询参数
+#[derive(Deserialize)]
+pub struct TransferQuery {
+    key: String,
+    app: String,
+    format: Option<String>, // 导入格式：jsonl（默认）或 disqus
+}
+
+// JSON Lines中的一条评论，回复通过parentId关联，likes为点赞人标识
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentRecord {
+    id: String,
+    topic: String,
+    content: String,
+    user: String,
+    #[serde(rename = "createTime")]
+    create_time: i64,
+    #[serde(default)]
+    like: i64,
+    #[serde(rename = "toUser", default)]
+    to_user: Option<String>,
+    #[serde(rename = "toContent", default)]
+    to_content: Option<String>,
+    #[serde(rename = "parentId", default)]
+    parent_id: Option<String>,
+    #[serde(default = "default_status")]
+    status: String,
+    #[serde(rename = "editTime", default)]
+    edit_time: Option<i64>,
+    #[serde(rename = "pinnedTime", default)]
+    pinned_time: Option<i64>,
+    #[serde(default)]
+    author: bool,
+    #[serde(default)]
+    verified: bool,
+    #[serde(default)]
+    likes: Vec<String>,
+}
+
+fn default_status() -> String {
+    STATUS_APPROVED.to_string()
+}
+
+// 导入结果
+#[derive(Serialize)]
+pub struct ImportResponse {
+    total: usize,
+    imported: u64,
+    skipped: u64, // 已存在而跳过的评论数
+}
+
+// 待导入的评论和点赞记录(评论id, 点赞人)
+struct ImportBatch {
+    comments: Vec<Comment>,
+    likes: Vec<(String, String)>,
+}
+
+// 转换评论为导出记录
+fn to_record(comment: Comment, likes: Vec<String>) -> CommentRecord {
+    CommentRecord {
+        id: comment.id,
+        topic: comment.topic,
+        content: comment.content,
+        user: comment.user,
+        create_time: comment.create_time,
+        like: comment.like,
+        to_user: comment.to_user,
+        to_content: comment.to_content,
+        parent_id: comment.parent_id,
+        status: comment.status,
+        edit_time: comment.edit_time,
+        pinned_time: comment.pinned_time,
+        author: comment.is_author,
+        verified: comment.verified,
+        likes,
+    }
+}
+
+// 解析JSON Lines，评论归入app
+fn parse_jsonl(body: &str, app: &str) -> Result<ImportBatch, String> {
+    let mut comments = Vec::new();
+    let mut likes = Vec::new();
+    for (i, line) in body.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: CommentRecord =
+            serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        if record.id.is_empty() || record.topic.is_empty() {
+            return Err(format!("line {}: id and topic required", i + 1));
+        }
+        if ![
+            STATUS_PENDING,
+            STATUS_APPROVED,
+            STATUS_REJECTED,
+            STATUS_SPAM,
+            STATUS_DELETED,
+            STATUS_HIDDEN,
+        ]
+        .contains(&record.status.as_str())
+        {
+            return Err(format!("line {}: invalid status {}", i + 1, record.status));
+        }
+
+        likes.extend(
+            record
+                .likes
+                .into_iter()
+                .map(|liker| (record.id.clone(), liker)),
+        );
+        comments.push(Comment {
+            id: record.id,
+            app: app.to_string(),
+            topic: record.topic,
+            content: record.content,
+            create_time: record.create_time,
+            user: record.user,
+            like: record.like,
+            to_user: record.to_user,
+            to_content: record.to_content,
+            parent_id: record.parent_id,
+            status: record.status,
+            edit_time: record.edit_time,
+            pinned_time: record.pinned_time,
+            is_author: record.author,
+            verified: record.verified,
+        });
+    }
+    Ok(ImportBatch { comments, likes })
+}
+
+// 导出应用全部评论为JSON Lines的处理函数
+pub async fn export_comments(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<TransferQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let comments = match get_app_comments(pool.as_ref(), &query.app).await {
+        Ok(comments) => comments,
+        Err(e) => {
+            eprintln!("Error getting app comments: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to export comments".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    };
+    let mut likes: HashMap<String, Vec<String>> = HashMap::new();
+    match get_app_likes(pool.as_ref(), &query.app).await {
+        Ok(rows) => {
+            for (comment_id, liker) in rows {
+                likes.entry(comment_id).or_default().push(liker);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error getting app likes: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to export comments".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let mut body = String::new();
+    for comment in comments {
+        let comment_likes = likes.remove(&comment.id).unwrap_or_default();
+        let record = to_record(comment, comment_likes);
+        // CommentRecord只包含字符串和数字，序列化不会失败
+        body.push_str(&serde_json::to_string(&record).unwrap_or_default());
+        body.push('\n');
+    }
+
+    (
+        [
+            (
+                header::CONTENT_TYPE,
+                "application/x-ndjson; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}-comments.jsonl\"", query.app),
+            ),
+        ],
+        body,
+    )
+        .into_response()
+}
+
+// 导入评论的处理函数，支持JSON Lines和Disqus XML，按评论id去重，id被其他应用占用时返回409
+pub async fn import(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<TransferQuery>,
+    body: String,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match get_comment_app(pool.as_ref(), &query.app).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("应用不存在".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error getting comment app: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to import comments".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let parsed = match query.format.as_deref() {
+        None | Some("") | Some("jsonl") => parse_jsonl(&body, &query.app),
+        Some("disqus") => parse_disqus(&body, &query.app).map(|comments| ImportBatch {
+            comments,
+            likes: Vec::new(),
+        }),
+        Some(_) => Err("format must be jsonl or disqus".to_string()),
+    };
+    let batch = match parsed {
+        Ok(batch) => batch,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error(message)),
+            )
+                .into_response();
+        }
+    };
+
+    match import_comments(pool.as_ref(), &batch.comments, &batch.likes).await {
+        Ok(ImportOutcome::Imported(imported)) => Json(ApiResponse::data_success(ImportResponse {
+            total: batch.comments.len(),
+            imported,
+            skipped: batch.comments.len() as u64 - imported,
+        }))
+        .into_response(),
+        Ok(ImportOutcome::Conflict(id)) => (
+            StatusCode::CONFLICT,
+            Json(ApiResponse::<()>::error(format!(
+                "评论 {} 已属于其他应用",
+                id
+            ))),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error importing comments: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to import comments".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_jsonl_assigns_app_and_likes() {
+        let body = r#"{"id":"c1","topic":"/a","content":"hi","user":"u","createTime":1,"like":2,"likes":["u:x","u:y"]}
+
+{"id":"c2","topic":"/a","content":"re","user":"v","createTime":2,"parentId":"c1","status":"pending"}"#;
+        let batch = parse_jsonl(body, "blog").unwrap();
+        assert_eq!(batch.comments.len(), 2);
+        assert_eq!(batch.comments[0].app, "blog");
+        assert_eq!(batch.comments[0].status, STATUS_APPROVED);
+        assert_eq!(batch.comments[1].parent_id.as_deref(), Some("c1"));
+        assert_eq!(
+            batch.likes,
+            vec![
+                ("c1".to_string(), "u:x".to_string()),
+                ("c1".to_string(), "u:y".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_jsonl_reports_line() {
+        let body = "{\"id\":\"c1\",\"topic\":\"/a\",\"content\":\"\",\"user\":\"u\",\"createTime\":1,\"status\":\"bad\"}";
+        assert_eq!(
+            parse_jsonl(body, "blog").err().as_deref(),
+            Some("line 1: invalid status bad")
+        );
+        assert!(parse_jsonl("not json", "blog").is_err());
+    }
+}
@@ -0,0 +1,342 @@
+use axum::{
+    extract::{Json as AxumJson, Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use sqlx::types::Json as SqlxJson;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::{
+    AdminKeyQuery, ApiResponse, PageResponse, admin_forbidden, default_page, default_size,
+    is_admin_key,
+};
+use crate::dao::comment_app::get_comment_app;
+use crate::dao::webhook::{
+    CommentWebhook, WebhookSettings, delete_webhook, get_webhook, insert_webhook,
+    list_deliveries, list_webhooks, update_webhook,
+};
+use crate::util::token::random_token;
+use crate::util::webhook::WEBHOOK_EVENTS;
+
+// Webhook列表查询参数
+#[derive(Deserialize)]
+pub struct WebhookListQuery {
+    key: String,
+    app: Option<String>,
+}
+
+// 投递记录查询参数
+#[derive(Deserialize)]
+pub struct DeliveryQuery {
+    key: String,
+    status: Option<String>, // pending、success、failed
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 创建Webhook请求体
+#[derive(Deserialize)]
+pub struct CreateWebhookRequest {
+    app: String,
+    url: String,
+    events: Vec<String>,
+    secret: Option<String>, // 签名密钥，不传时自动生成
+}
+
+// 修改Webhook请求体，未传的字段保持不变
+#[derive(Deserialize)]
+pub struct UpdateWebhookRequest {
+    url: Option<String>,
+    events: Option<Vec<String>>,
+    enabled: Option<bool>,
+}
+
+// 创建Webhook响应，签名密钥只在创建时返回一次
+#[derive(Serialize)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    webhook: CommentWebhook,
+    secret: String,
+}
+
+// 校验Webhook设置，不合法时返回错误信息
+fn webhook_error(url: Option<&str>, events: Option<&[String]>) -> Option<String> {
+    if let Some(url) = url
+        && !matches!(reqwest::Url::parse(url), Ok(u) if u.scheme() == "http" || u.scheme() == "https")
+    {
+        return Some("url must be an http or https URL".to_string());
+    }
+    if let Some(events) = events {
+        if events.is_empty() {
+            return Some("events must not be empty".to_string());
+        }
+        if let Some(event) = events.iter().find(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+            return Some(format!(
+                "unknown event: {}, must be one of {}",
+                event,
+                WEBHOOK_EVENTS.join(", ")
+            ));
+        }
+    }
+    None
+}
+
+// 查询Webhook列表的处理函数
+pub async fn get_webhooks(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<WebhookListQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match list_webhooks(pool.as_ref(), query.app.as_deref()).await {
+        Ok(webhooks) => Json(ApiResponse::data_success(webhooks)).into_response(),
+        Err(e) => {
+            eprintln!("Error getting webhooks: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get webhooks".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 创建Webhook的处理函数，返回签名密钥
+pub async fn create(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<CreateWebhookRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if let Some(message) = webhook_error(Some(&body.url), Some(&body.events)) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    match get_comment_app(pool.as_ref(), &body.app).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("应用不存在".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error getting comment app: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to create webhook".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let mut events = body.events;
+    events.sort();
+    events.dedup();
+    let secret = body
+        .secret
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(random_token);
+    let webhook = CommentWebhook {
+        id: Uuid::new_v4().to_string(),
+        app: body.app,
+        url: body.url,
+        secret: secret.clone(),
+        events: SqlxJson(events),
+        enabled: true,
+        create_time: Utc::now().timestamp(),
+    };
+
+    match insert_webhook(pool.as_ref(), &webhook).await {
+        Ok(()) => Json(ApiResponse::data_success(CreateWebhookResponse { webhook, secret }))
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error inserting webhook: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to create webhook".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 修改Webhook地址、订阅事件或启用状态的处理函数
+pub async fn update(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<UpdateWebhookRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if let Some(message) = webhook_error(body.url.as_deref(), body.events.as_deref()) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(message)),
+        )
+            .into_response();
+    }
+
+    let settings = WebhookSettings {
+        url: body.url,
+        events: body.events.map(|mut events| {
+            events.sort();
+            events.dedup();
+            events
+        }),
+        enabled: body.enabled,
+    };
+
+    match update_webhook(pool.as_ref(), &id, &settings).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error updating webhook: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to update webhook".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 删除Webhook及其投递记录的处理函数
+pub async fn delete(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match delete_webhook(pool.as_ref(), &id).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error deleting webhook: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to delete webhook".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 查询Webhook投递记录的处理函数
+pub async fn get_deliveries(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<DeliveryQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match get_webhook(pool.as_ref(), &id).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse::<()>::error("未找到".to_string())),
+            )
+                .into_response();
+        }
+        Err(e) => {
+            eprintln!("Error getting webhook: {:?}", e);
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get webhook deliveries".to_string(),
+                )),
+            )
+                .into_response();
+        }
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    let status = query.status.as_deref().filter(|s| !s.is_empty());
+    match list_deliveries(pool.as_ref(), &id, status, page, size).await {
+        Ok((items, total)) => Json(ApiResponse::data_success(PageResponse {
+            total,
+            page,
+            size,
+            items,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error getting webhook deliveries: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get webhook deliveries".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_error() {
+        let events = vec!["comment.created".to_string(), "comment.liked".to_string()];
+        assert!(webhook_error(Some("https://example.com/hook"), Some(&events)).is_none());
+        assert!(webhook_error(None, None).is_none());
+        assert!(webhook_error(Some("ftp://example.com"), None).is_some());
+        assert!(webhook_error(None, Some(&[])).is_some());
+        assert!(webhook_error(None, Some(&["comment.edited".to_string()])).is_some());
+    }
+}
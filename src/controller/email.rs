@@ -1,13 +1,27 @@
 use axum::{
-    extract::Json as AxumJson,
+    extract::{Json as AxumJson, Path, Query, State},
     http::StatusCode,
-    response::{IntoResponse, Json},
+    response::{Html, IntoResponse, Json},
 };
+use chrono::Utc;
 use serde::Deserialize;
+use sqlx::SqlitePool;
 use std::env;
+use std::sync::Arc;
+use uuid::Uuid;
 
-use super::ApiResponse;
-use crate::util::email::{EmailConfig, send_email};
+use super::{
+    AdminKeyQuery, ApiResponse, PageResponse, admin_forbidden, default_page, default_size,
+    is_admin_key,
+};
+use crate::dao::email_log::{EmailLogFilter, search_email_logs};
+use crate::dao::scheduled_email::{
+    ScheduledEmail, cancel_scheduled_email, get_scheduled_emails_by_key, insert_scheduled_email,
+};
+use crate::dao::suppression::{add_suppression, get_suppressions, remove_suppression};
+use crate::util::email::{
+    EmailConfig, key_fingerprint, send_email, validate_address, verify_unsubscribe_token,
+};
 
 #[derive(Deserialize)]
 pub struct EmailRequest {
@@ -15,9 +29,72 @@ pub struct EmailRequest {
     subject: Option<String>,
     content: String,
     to: Option<String>,
+    #[serde(rename = "sendAt")]
+    send_at: Option<i64>, // 定时发送时间（Unix秒），为空或已过期则立即发送
+}
+
+// 定时邮件查询参数
+#[derive(Deserialize)]
+pub struct ScheduledEmailQuery {
+    key: String,
+}
+
+// 邮件发送记录查询参数
+#[derive(Deserialize)]
+pub struct EmailLogQuery {
+    key: String,
+    recipient: Option<String>,
+    subject: Option<String>,
+    template: Option<String>,
+    #[serde(rename = "apiKey")]
+    api_key: Option<String>,
+    status: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 退订链接参数
+#[derive(Deserialize)]
+pub struct UnsubscribeQuery {
+    email: String,
+    token: String,
 }
 
-pub async fn send_email_handler(AxumJson(req): AxumJson<EmailRequest>) -> impl IntoResponse {
+// 屏蔽名单查询参数
+#[derive(Deserialize)]
+pub struct SuppressionQuery {
+    key: String,
+    email: Option<String>,
+    #[serde(default = "default_page")]
+    page: i64,
+    #[serde(default = "default_size")]
+    size: i64,
+}
+
+// 添加屏蔽名单的请求体
+#[derive(Deserialize)]
+pub struct AddSuppressionRequest {
+    email: String,
+    reason: Option<String>,
+}
+
+// 定时发送的最远时间（秒）
+const MAX_SCHEDULE_SECONDS: i64 = 90 * 24 * 3600;
+
+// 验证邮件发送key，未配置MAIL_PASSWORD时拒绝所有请求
+fn is_mail_key(key: &str) -> bool {
+    let mail_password = env::var("MAIL_PASSWORD").unwrap_or_default();
+    !mail_password.is_empty() && key == mail_password
+}
+
+pub async fn send_email_handler(
+    State(pool): State<Arc<SqlitePool>>,
+    AxumJson(req): AxumJson<EmailRequest>,
+) -> impl IntoResponse {
     // 验证content是否存在
     if req.content.is_empty() {
         return (
@@ -28,8 +105,7 @@ pub async fn send_email_handler(AxumJson(req): AxumJson<EmailRequest>) -> impl I
     }
 
     // 验证key是否正确
-    let mail_password = env::var("MAIL_PASSWORD").unwrap_or_default();
-    if req.key != mail_password {
+    if !is_mail_key(&req.key) {
         return (
             StatusCode::FORBIDDEN,
             Json(ApiResponse::<()>::error("invalid key".to_string())),
@@ -37,11 +113,70 @@ pub async fn send_email_handler(AxumJson(req): AxumJson<EmailRequest>) -> impl I
             .into_response();
     }
 
+    // 验证收件人地址
+    if let Some(to) = &req.to
+        && let Err(e) = validate_address(to)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(format!("invalid to: {}", e))),
+        )
+            .into_response();
+    }
+
+    let now = Utc::now().timestamp();
+    if req
+        .send_at
+        .is_some_and(|send_at| send_at > now + MAX_SCHEDULE_SECONDS)
+    {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error(
+                "sendAt must be within 90 days".to_string(),
+            )),
+        )
+            .into_response();
+    }
+
     // 创建邮件配置
-    let config = EmailConfig::new(req.subject.clone(), req.content.clone(), req.to.clone());
+    let config = EmailConfig::new(req.subject.clone(), req.content.clone(), req.to.clone())
+        .with_template("api")
+        .with_api_key(&req.key);
+
+    // 定时发送：保存后由后台任务发送
+    if let Some(send_at) = req.send_at
+        && send_at > now
+    {
+        let scheduled = ScheduledEmail {
+            id: Uuid::new_v4().to_string(),
+            api_key: config.api_key.clone().unwrap_or_default(),
+            recipient: config.to,
+            subject: config.subject,
+            content: config.content,
+            send_at,
+            status: "pending".to_string(),
+            error: None,
+            create_time: now,
+            update_time: now,
+        };
+
+        return match insert_scheduled_email(pool.as_ref(), &scheduled).await {
+            Ok(id) => Json(ApiResponse::data_success(id)).into_response(),
+            Err(e) => {
+                eprintln!("Error scheduling email: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiResponse::<()>::error(
+                        "Failed to schedule email".to_string(),
+                    )),
+                )
+                    .into_response()
+            }
+        };
+    }
 
     // 发送邮件
-    match send_email(config).await {
+    match send_email(pool.as_ref(), config).await {
         Ok(_) => Json(ApiResponse::<()>::message_success("ok".to_string())).into_response(),
         Err(e) => {
             eprintln!("Error sending email: {:?}", e);
@@ -53,3 +188,286 @@ pub async fn send_email_handler(AxumJson(req): AxumJson<EmailRequest>) -> impl I
         }
     }
 }
+
+// 获取本key创建的定时邮件列表的处理函数
+pub async fn get_scheduled_emails(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<ScheduledEmailQuery>,
+) -> impl IntoResponse {
+    // 验证key是否正确
+    if !is_mail_key(&query.key) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error("invalid key".to_string())),
+        )
+            .into_response();
+    }
+
+    match get_scheduled_emails_by_key(pool.as_ref(), &key_fingerprint(&query.key)).await {
+        Ok(emails) => Json(ApiResponse::data_success(emails)).into_response(),
+        Err(e) => {
+            eprintln!("Error getting scheduled emails: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get scheduled emails".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 取消定时邮件的处理函数
+pub async fn cancel_scheduled_email_handler(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(id): Path<String>,
+    Query(query): Query<ScheduledEmailQuery>,
+) -> impl IntoResponse {
+    // 验证key是否正确
+    if !is_mail_key(&query.key) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error("invalid key".to_string())),
+        )
+            .into_response();
+    }
+
+    match cancel_scheduled_email(
+        pool.as_ref(),
+        &id,
+        &key_fingerprint(&query.key),
+        Utc::now().timestamp(),
+    )
+    .await
+    {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::<()>::message_success("cancelled".to_string())).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到待发送的邮件".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error cancelling scheduled email: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to cancel scheduled email".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 查询邮件发送记录的处理函数（管理员）
+pub async fn get_email_logs(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<EmailLogQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    let filter = EmailLogFilter {
+        recipient: query.recipient,
+        subject: query.subject,
+        template: query.template,
+        // 日志中只保存密钥指纹，按指纹检索
+        api_key: query.api_key.as_deref().map(key_fingerprint),
+        status: query.status,
+        from: query.from,
+        to: query.to,
+    };
+
+    match search_email_logs(pool.as_ref(), &filter, page, size).await {
+        Ok((items, total)) => Json(ApiResponse::data_success(PageResponse {
+            total,
+            page,
+            size,
+            items,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error searching email logs: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to search email logs".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 转义HTML特殊字符
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// 退订确认页面的处理函数（GET 打开链接）
+//
+// 只展示确认按钮，不修改状态，避免邮件安全扫描和链接预取误触发退订。
+pub async fn unsubscribe_page(Query(query): Query<UnsubscribeQuery>) -> impl IntoResponse {
+    if !verify_unsubscribe_token(&query.email, &query.token) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Html("<p>退订链接无效或已损坏。</p>".to_string()),
+        )
+            .into_response();
+    }
+
+    // action为空时表单提交到当前地址，保留链接中的email和token参数
+    Html(format!(
+        "<form method=\"post\" action=\"\"><p>确认退订 {} ？退订后不会再收到来自 wycode.cn 的通知邮件。</p><button type=\"submit\">确认退订</button></form>",
+        escape_html(&query.email)
+    ))
+    .into_response()
+}
+
+// 退订的处理函数（POST 为确认页面提交或邮件客户端的一键退订，RFC 8058）
+pub async fn unsubscribe(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<UnsubscribeQuery>,
+) -> impl IntoResponse {
+    if !verify_unsubscribe_token(&query.email, &query.token) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Html("<p>退订链接无效或已损坏。</p>".to_string()),
+        )
+            .into_response();
+    }
+
+    match add_suppression(
+        pool.as_ref(),
+        &query.email,
+        "unsubscribe",
+        Utc::now().timestamp(),
+    )
+    .await
+    {
+        Ok(_) => Html(format!(
+            "<p>{} 已退订，之后不会再收到来自 wycode.cn 的通知邮件。</p>",
+            escape_html(&query.email)
+        ))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error adding suppression: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html("<p>退订失败，请稍后重试。</p>".to_string()),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 查询屏蔽名单的处理函数（管理员）
+pub async fn get_suppression_list(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<SuppressionQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    let page = query.page.max(1);
+    let size = query.size.clamp(1, 100);
+    match get_suppressions(pool.as_ref(), query.email.as_deref(), page, size).await {
+        Ok((items, total)) => Json(ApiResponse::data_success(PageResponse {
+            total,
+            page,
+            size,
+            items,
+        }))
+        .into_response(),
+        Err(e) => {
+            eprintln!("Error getting suppressions: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to get suppressions".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 添加屏蔽名单的处理函数（管理员）
+pub async fn add_suppression_handler(
+    State(pool): State<Arc<SqlitePool>>,
+    Query(query): Query<AdminKeyQuery>,
+    AxumJson(body): AxumJson<AddSuppressionRequest>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    if body.email.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::<()>::error("email required".to_string())),
+        )
+            .into_response();
+    }
+
+    let reason = body.reason.unwrap_or("admin".to_string());
+    match add_suppression(pool.as_ref(), &body.email, &reason, Utc::now().timestamp()).await {
+        Ok(rows_affected) => Json(ApiResponse::data_success(rows_affected)).into_response(),
+        Err(e) => {
+            eprintln!("Error adding suppression: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to add suppression".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
+
+// 移除屏蔽名单的处理函数（管理员）
+pub async fn remove_suppression_handler(
+    State(pool): State<Arc<SqlitePool>>,
+    Path(email): Path<String>,
+    Query(query): Query<AdminKeyQuery>,
+) -> impl IntoResponse {
+    // 验证管理员key
+    if !is_admin_key(&query.key) {
+        return admin_forbidden();
+    }
+
+    match remove_suppression(pool.as_ref(), &email).await {
+        Ok(rows_affected) if rows_affected > 0 => {
+            Json(ApiResponse::data_success(rows_affected)).into_response()
+        }
+        Ok(_) => (
+            StatusCode::NOT_FOUND,
+            Json(ApiResponse::<()>::error("未找到".to_string())),
+        )
+            .into_response(),
+        Err(e) => {
+            eprintln!("Error removing suppression: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse::<()>::error(
+                    "Failed to remove suppression".to_string(),
+                )),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,4 +1,9 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
 use serde::{Deserialize, Serialize};
+use std::env;
 
 #[derive(Serialize, Deserialize)]
 pub struct ApiResponse<T> {
@@ -33,9 +38,54 @@ impl<T> ApiResponse<T> {
     }
 }
 
+/// 校验管理员密钥（环境变量 `ADMIN_KEY`），未配置时一律拒绝
+pub fn is_admin_key(key: &str) -> bool {
+    let admin_key = env::var("ADMIN_KEY").unwrap_or_default();
+    !admin_key.is_empty() && key == admin_key
+}
+
+/// 管理员key无效时的403响应
+pub fn admin_forbidden() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error("invalid key".to_string())),
+    )
+        .into_response()
+}
+
+// 管理员key参数
+#[derive(Deserialize)]
+pub struct AdminKeyQuery {
+    pub key: String,
+}
+
+pub fn default_page() -> i64 {
+    1
+}
+
+pub fn default_size() -> i64 {
+    20
+}
+
+// 分页查询结果
+#[derive(Serialize, Deserialize)]
+pub struct PageResponse<T> {
+    pub total: i64,
+    pub page: i64,
+    pub size: i64,
+    pub items: Vec<T>,
+}
+
 pub mod blog;
 pub mod clipboard;
 pub mod comment;
+pub mod comment_admin;
+pub mod comment_app;
+pub mod comment_feed;
+pub mod comment_identity;
+pub mod comment_topic;
+pub mod comment_transfer;
+pub mod comment_webhook;
 pub mod config;
 pub mod coze;
 pub mod email;
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+// 评论屏蔽规则数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct BlockRule {
+    pub id: i64,
+    pub pattern: String,
+    #[serde(rename = "isRegex")]
+    pub is_regex: bool,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+}
+
+// 获取所有屏蔽规则
+pub async fn get_block_rules(pool: &SqlitePool) -> Result<Vec<BlockRule>, sqlx::Error> {
+    let rules = sqlx::query_as(
+        "SELECT id, pattern, is_regex, create_time FROM comment_blocklist ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rules)
+}
+
+// 添加屏蔽规则，返回规则id
+pub async fn insert_block_rule(
+    pool: &SqlitePool,
+    pattern: &str,
+    is_regex: bool,
+    create_time: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO comment_blocklist (pattern, is_regex, create_time) VALUES (?, ?, ?)",
+    )
+    .bind(pattern)
+    .bind(is_regex)
+    .bind(create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// 删除屏蔽规则
+pub async fn delete_block_rule(pool: &SqlitePool, id: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM comment_blocklist WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
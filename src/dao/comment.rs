@@ -1,8 +1,19 @@
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
+use sqlx::{FromRow, QueryBuilder, SqlitePool};
+use std::collections::HashSet;
+
+use super::reaction::ReactionSummary;
+
+// 评论审核状态
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_REJECTED: &str = "rejected";
+pub const STATUS_SPAM: &str = "spam";
+pub const STATUS_DELETED: &str = "deleted";
+pub const STATUS_HIDDEN: &str = "hidden"; // 被举报次数达到阈值后自动隐藏
 
 // Comment数据结构
-#[derive(Debug, FromRow, Serialize, Deserialize)]
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Comment {
     pub id: String,
     pub app: String,
@@ -13,6 +24,46 @@ pub struct Comment {
     pub like: i64,
     pub to_user: Option<String>,
     pub to_content: Option<String>,
+    pub parent_id: Option<String>,
+    pub status: String,
+    pub edit_time: Option<i64>,
+    pub pinned_time: Option<i64>,
+    pub is_author: bool,
+    pub verified: bool,
+}
+
+// 查询评论时的字段列表
+pub(super) const COMMENT_COLUMNS: &str = "id, app, topic, content, create_time, user, like, to_user, to_content, parent_id, status, edit_time, pinned_time, is_author, verified";
+
+// 回复树的顶层评论：已公开的顶层评论，以及仍有回复的已删除、隐藏的顶层评论（显示为占位，保留其下的回复）
+const THREAD_ROOT_CONDITION: &str = "parent_id IS NULL AND (status = 'approved' OR EXISTS (SELECT 1 FROM comment r WHERE r.parent_id = comment.id))";
+
+// 管理员评论查询条件
+#[derive(Debug, Default)]
+pub struct CommentFilter {
+    pub app: Option<String>,
+    pub topic: Option<String>,
+    pub status: Option<String>,
+}
+
+// 管理员全文搜索条件
+#[derive(Debug, Default)]
+pub struct CommentSearch {
+    pub query: String,
+    pub app: Option<String>,
+    pub topic: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<i64>, // 起始时间（秒，含）
+    pub to: Option<i64>,   // 结束时间（秒，不含）
+}
+
+// 全文搜索结果，snippet中的命中部分用util::highlight的标记包裹
+#[derive(Debug, FromRow, Serialize)]
+pub struct CommentSearchHit {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub comment: Comment,
+    pub snippet: String,
 }
 
 // To响应数据结构
@@ -32,46 +83,412 @@ pub struct CommentResponse {
     pub like: i64,
     #[serde(rename = "createTime")]
     pub create_time: String,
+    #[serde(rename = "createTimeIso")]
+    pub create_time_iso: String,
+    #[serde(rename = "createTimestamp")]
+    pub create_timestamp: i64,
     pub to: Option<ToResponse>,
+    #[serde(rename = "parentId", skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replies: Option<Vec<CommentResponse>>,
+    #[serde(rename = "likedByMe", skip_serializing_if = "Option::is_none")]
+    pub liked_by_me: Option<bool>,
+    pub edited: bool,
+    pub pinned: bool,
+    pub author: bool,
+    pub verified: bool,
+    pub deleted: bool, // 回复树中已删除或隐藏的评论占位，只保留回复
+    #[serde(rename = "contentHtml", skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reactions: Vec<ReactionSummary>,
+    #[serde(rename = "myReactions", skip_serializing_if = "Option::is_none")]
+    pub my_reactions: Option<Vec<String>>,
+}
+
+// 评论编辑历史数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CommentEdit {
+    pub id: i64,
+    #[serde(rename = "commentId")]
+    pub comment_id: String,
+    pub content: String,
+    #[serde(rename = "editTime")]
+    pub edit_time: i64,
+}
+
+// 话题评论统计数据结构
+#[derive(Debug, FromRow)]
+pub struct TopicCount {
+    pub topic: String,
+    pub count: i64,
+    pub latest_time: i64,
+}
+
+// 话题评论统计响应数据结构
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TopicCountResponse {
+    pub topic: String,
+    pub count: i64,
+    #[serde(rename = "latestTime")]
+    pub latest_time: Option<String>,
+    #[serde(rename = "latestTimeIso")]
+    pub latest_time_iso: Option<String>,
+    #[serde(rename = "latestTimestamp")]
+    pub latest_timestamp: Option<i64>,
 }
 
-// 验证app和key是否匹配
-pub async fn validate_app_key(
+// 发表评论响应数据结构，editToken只在发表时返回一次
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostCommentResponse {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(rename = "editToken")]
+    pub edit_token: String,
+}
+
+// 分页评论列表响应数据结构
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentPage {
+    pub comments: Vec<CommentResponse>,
+    pub total: i64,
+    #[serde(rename = "nextCursor")]
+    pub next_cursor: Option<String>,
+}
+
+/// 评论排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentSort {
+    /// 最新优先
+    Newest,
+    /// 最早优先
+    Oldest,
+    /// 点赞最多优先
+    MostLiked,
+}
+
+impl CommentSort {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "newest" => Some(CommentSort::Newest),
+            "oldest" => Some(CommentSort::Oldest),
+            "likes" => Some(CommentSort::MostLiked),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            CommentSort::Newest => "newest",
+            CommentSort::Oldest => "oldest",
+            CommentSort::MostLiked => "likes",
+        }
+    }
+}
+
+/// 分页游标，记录上一页最后一条评论的排序键
+#[derive(Debug, PartialEq, Eq)]
+pub struct CommentCursor {
+    pub sort: CommentSort,
+    pub like: i64,
+    pub create_time: i64,
+    pub id: String,
+}
+
+impl CommentCursor {
+    pub fn from_comment(sort: CommentSort, comment: &Comment) -> Self {
+        CommentCursor {
+            sort,
+            like: comment.like,
+            create_time: comment.create_time,
+            id: comment.id.clone(),
+        }
+    }
+
+    /// 编码为不透明字符串
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}|{}|{}|{}",
+            self.sort.as_str(),
+            self.like,
+            self.create_time,
+            self.id
+        ))
+    }
+
+    /// 解码游标，格式不正确时返回None
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let raw = String::from_utf8(hex::decode(cursor).ok()?).ok()?;
+        let mut parts = raw.splitn(4, '|');
+        Some(CommentCursor {
+            sort: CommentSort::parse(parts.next()?)?,
+            like: parts.next()?.parse().ok()?,
+            create_time: parts.next()?.parse().ok()?,
+            id: parts.next()?.to_string(),
+        })
+    }
+}
+
+/// 评论列表查询选项
+pub struct CommentListOptions {
+    pub sort: CommentSort,
+    pub cursor: Option<CommentCursor>,
+    /// 返回条数上限，为空时返回全部
+    pub limit: Option<i64>,
+    /// 只返回顶层评论（不含回复）
+    pub roots_only: bool,
+}
+
+// 根据app和topic分页获取评论列表
+pub async fn list_comments(
     pool: &SqlitePool,
     app_id: &str,
-    key: &str,
-) -> Result<bool, sqlx::Error> {
-    let result =
-        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM comment_apps WHERE id = ? AND key = ?")
-            .bind(app_id)
-            .bind(key)
-            .fetch_one(pool)
-            .await?;
+    topic: &str,
+    options: &CommentListOptions,
+) -> Result<Vec<Comment>, sqlx::Error> {
+    let mut builder = QueryBuilder::new(format!("SELECT {} FROM comment WHERE app = ", COMMENT_COLUMNS));
+    builder.push_bind(app_id.to_string());
+    builder.push(" AND topic = ").push_bind(topic.to_string());
+    // 已公开的置顶评论由get_pinned_comments单独返回
+    if options.roots_only {
+        builder.push(format!(
+            " AND {} AND (pinned_time IS NULL OR status != 'approved')",
+            THREAD_ROOT_CONDITION
+        ));
+    } else {
+        builder.push(" AND status = ").push_bind(STATUS_APPROVED);
+        builder.push(" AND pinned_time IS NULL");
+    }
 
-    Ok(result > 0)
+    // 游标条件：排在游标之后的评论
+    if let Some(cursor) = &options.cursor {
+        match options.sort {
+            CommentSort::Newest => {
+                builder
+                    .push(" AND (create_time < ")
+                    .push_bind(cursor.create_time)
+                    .push(" OR (create_time = ")
+                    .push_bind(cursor.create_time)
+                    .push(" AND id < ")
+                    .push_bind(cursor.id.clone())
+                    .push("))");
+            }
+            CommentSort::Oldest => {
+                builder
+                    .push(" AND (create_time > ")
+                    .push_bind(cursor.create_time)
+                    .push(" OR (create_time = ")
+                    .push_bind(cursor.create_time)
+                    .push(" AND id > ")
+                    .push_bind(cursor.id.clone())
+                    .push("))");
+            }
+            CommentSort::MostLiked => {
+                builder
+                    .push(" AND (like < ")
+                    .push_bind(cursor.like)
+                    .push(" OR (like = ")
+                    .push_bind(cursor.like)
+                    .push(" AND (create_time < ")
+                    .push_bind(cursor.create_time)
+                    .push(" OR (create_time = ")
+                    .push_bind(cursor.create_time)
+                    .push(" AND id < ")
+                    .push_bind(cursor.id.clone())
+                    .push("))))");
+            }
+        }
+    }
+
+    builder.push(match options.sort {
+        CommentSort::Newest => " ORDER BY create_time DESC, id DESC",
+        CommentSort::Oldest => " ORDER BY create_time ASC, id ASC",
+        CommentSort::MostLiked => " ORDER BY like DESC, create_time DESC, id DESC",
+    });
+    if let Some(limit) = options.limit {
+        builder.push(" LIMIT ").push_bind(limit);
+    }
+
+    let comments = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok(comments)
 }
 
-// 根据app和topic获取评论列表
-pub async fn get_comments_by_app_topic(
+// 获取话题下已公开的置顶评论，按置顶时间倒序
+pub async fn get_pinned_comments(
     pool: &SqlitePool,
     app_id: &str,
     topic: &str,
+    roots_only: bool,
 ) -> Result<Vec<Comment>, sqlx::Error> {
-    let comments = sqlx::query_as(
-        "SELECT id, app, topic, content, create_time, user, like, to_user, to_content FROM comment WHERE app = ? AND topic = ? ORDER BY create_time DESC",
+    let comments = sqlx::query_as(&format!(
+        "SELECT {} FROM comment WHERE app = ? AND topic = ? AND status = 'approved' AND pinned_time IS NOT NULL AND (? = 0 OR parent_id IS NULL) ORDER BY pinned_time DESC, id DESC",
+        COMMENT_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(topic)
+    .bind(roots_only)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(comments)
+}
+
+// 置顶或取消置顶评论，pinned_time为空时取消；只能置顶已公开的评论
+pub async fn set_comment_pinned(
+    pool: &SqlitePool,
+    id: &str,
+    pinned_time: Option<i64>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE comment SET pinned_time = ? WHERE id = ? AND (? IS NULL OR status = 'approved')",
     )
+    .bind(pinned_time)
+    .bind(id)
+    .bind(pinned_time)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 统计话题下的评论数，roots_only时统计回复树的顶层评论数
+pub async fn count_comments(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+    roots_only: bool,
+) -> Result<i64, sqlx::Error> {
+    let sql = if roots_only {
+        format!(
+            "SELECT COUNT(*) FROM comment WHERE app = ? AND topic = ? AND {}",
+            THREAD_ROOT_CONDITION
+        )
+    } else {
+        "SELECT COUNT(*) FROM comment WHERE app = ? AND topic = ? AND status = 'approved'".to_string()
+    };
+    let count = sqlx::query_scalar::<_, i64>(&sql)
         .bind(app_id)
         .bind(topic)
-        .fetch_all(pool)
+        .fetch_one(pool)
         .await?;
 
+    Ok(count)
+}
+
+// 批量统计多个话题的已公开评论数和最新评论时间，没有评论的话题不返回
+pub async fn count_comments_by_topics(
+    pool: &SqlitePool,
+    app_id: &str,
+    topics: &[String],
+) -> Result<Vec<TopicCount>, sqlx::Error> {
+    if topics.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT topic, COUNT(*) AS count, MAX(create_time) AS latest_time FROM comment WHERE app = ",
+    );
+    builder.push_bind(app_id.to_string());
+    builder.push(" AND status = ").push_bind(STATUS_APPROVED);
+    builder.push(" AND topic IN (");
+    let mut separated = builder.separated(", ");
+    for topic in topics {
+        separated.push_bind(topic.clone());
+    }
+    separated.push_unseparated(") GROUP BY topic");
+
+    let counts = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok(counts)
+}
+
+// 获取应用（或指定话题）下最新的已公开评论
+pub async fn get_latest_comments(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Comment>, sqlx::Error> {
+    let mut builder = QueryBuilder::new(format!("SELECT {} FROM comment WHERE app = ", COMMENT_COLUMNS));
+    builder.push_bind(app_id.to_string());
+    builder.push(" AND status = ").push_bind(STATUS_APPROVED);
+    if let Some(topic) = topic {
+        builder.push(" AND topic = ").push_bind(topic.to_string());
+    }
+    builder.push(" ORDER BY create_time DESC, id DESC LIMIT ").push_bind(limit);
+
+    let comments = builder.build_query_as().fetch_all(pool).await?;
+
     Ok(comments)
 }
 
+// 获取指定顶层评论下的所有后代回复（含未公开的，用于保留已删除评论下的回复），按时间正序
+pub async fn get_thread_replies(
+    pool: &SqlitePool,
+    root_ids: &[String],
+) -> Result<Vec<Comment>, sqlx::Error> {
+    if root_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "WITH RECURSIVE thread(id) AS (SELECT id FROM comment WHERE parent_id IN (",
+    );
+    let mut separated = builder.separated(", ");
+    for id in root_ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(format!(
+        ") UNION SELECT c.id FROM comment c JOIN thread t ON c.parent_id = t.id) SELECT {} FROM comment WHERE id IN (SELECT id FROM thread) ORDER BY create_time ASC, id ASC",
+        COMMENT_COLUMNS
+    ));
+
+    let comments = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok(comments)
+}
+
+// 获取话题下指定时间之后的评论内容，用于重复内容检测；排除被检测的评论本身，修改评论时不和自己比较
+pub async fn get_recent_topic_contents(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+    since: i64,
+    exclude_id: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+    let contents = sqlx::query_scalar(
+        "SELECT content FROM comment WHERE app = ? AND topic = ? AND create_time >= ? AND id != ?",
+    )
+    .bind(app_id)
+    .bind(topic)
+    .bind(since)
+    .bind(exclude_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(contents)
+}
+
+// 根据id获取评论
+pub async fn get_comment_by_id(pool: &SqlitePool, id: &str) -> Result<Option<Comment>, sqlx::Error> {
+    let comment = sqlx::query_as(&format!("SELECT {} FROM comment WHERE id = ?", COMMENT_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(comment)
+}
+
 // 插入新评论
-pub async fn insert_comment(pool: &SqlitePool, comment: &Comment) -> Result<String, sqlx::Error> {
+pub async fn insert_comment(
+    pool: &SqlitePool,
+    comment: &Comment,
+    edit_token_hash: Option<&str>,
+) -> Result<String, sqlx::Error> {
     sqlx::query(
-        "INSERT INTO comment (id, app, topic, content, create_time, user, like, to_user, to_content) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO comment (id, app, topic, content, create_time, user, like, to_user, to_content, parent_id, status, is_author, verified, edit_token_hash) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&comment.id)
     .bind(&comment.app)
@@ -82,18 +499,674 @@ pub async fn insert_comment(pool: &SqlitePool, comment: &Comment) -> Result<Stri
     .bind(comment.like)
     .bind(&comment.to_user)
     .bind(&comment.to_content)
+    .bind(&comment.parent_id)
+    .bind(&comment.status)
+    .bind(comment.is_author)
+    .bind(comment.verified)
+    .bind(edit_token_hash)
     .execute(pool)
     .await?;
 
     Ok(comment.id.clone())
 }
 
-// 更新评论点赞数
-pub async fn update_comment_like(pool: &SqlitePool, comment_id: &str) -> Result<u64, sqlx::Error> {
-    let result = sqlx::query("UPDATE comment SET like = like + 1 WHERE id = ?")
+// 获取应用的全部评论（含回复和各状态），按时间正序，用于导出
+pub async fn get_app_comments(pool: &SqlitePool, app_id: &str) -> Result<Vec<Comment>, sqlx::Error> {
+    let comments = sqlx::query_as(&format!(
+        "SELECT {} FROM comment WHERE app = ? ORDER BY create_time ASC, id ASC",
+        COMMENT_COLUMNS
+    ))
+    .bind(app_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(comments)
+}
+
+// 获取应用评论的点赞记录，返回(评论id, 点赞人)
+pub async fn get_app_likes(
+    pool: &SqlitePool,
+    app_id: &str,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let likes = sqlx::query_as(
+        "SELECT l.comment_id, l.liker FROM comment_likes l JOIN comment c ON c.id = l.comment_id WHERE c.app = ? ORDER BY l.create_time ASC",
+    )
+    .bind(app_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(likes)
+}
+
+// 导入结果
+#[derive(Debug, PartialEq)]
+pub enum ImportOutcome {
+    Imported(u64),    // 新导入的评论数
+    Conflict(String), // 与其他应用评论id冲突的评论id
+}
+
+// 导入评论及点赞记录，本应用已存在的评论跳过，id被其他应用占用时整批不导入
+pub async fn import_comments(
+    pool: &SqlitePool,
+    comments: &[Comment],
+    likes: &[(String, String)],
+) -> Result<ImportOutcome, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let mut imported = HashSet::new();
+    for comment in comments {
+        let existing: Option<String> = sqlx::query_scalar("SELECT app FROM comment WHERE id = ?")
+            .bind(&comment.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        match existing {
+            Some(app) if app == comment.app => continue,
+            // id已被其他应用占用，放弃整批导入
+            Some(_) => return Ok(ImportOutcome::Conflict(comment.id.clone())),
+            None => {}
+        }
+
+        sqlx::query(
+            "INSERT INTO comment (id, app, topic, content, create_time, user, like, to_user, to_content, parent_id, status, edit_time, pinned_time, is_author, verified) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&comment.id)
+        .bind(&comment.app)
+        .bind(&comment.topic)
+        .bind(&comment.content)
+        .bind(comment.create_time)
+        .bind(&comment.user)
+        .bind(comment.like)
+        .bind(&comment.to_user)
+        .bind(&comment.to_content)
+        .bind(&comment.parent_id)
+        .bind(&comment.status)
+        .bind(comment.edit_time)
+        .bind(comment.pinned_time)
+        .bind(comment.is_author)
+        .bind(comment.verified)
+        .execute(&mut *tx)
+        .await?;
+        imported.insert(comment.id.as_str());
+    }
+
+    // 点赞数已随评论导入，这里只补充本次新导入评论的去重记录
+    for (comment_id, liker) in likes {
+        if !imported.contains(comment_id.as_str()) {
+            continue;
+        }
+        sqlx::query(
+            "INSERT OR IGNORE INTO comment_likes (comment_id, liker, create_time) SELECT id, ?, create_time FROM comment WHERE id = ?",
+        )
+        .bind(liker)
         .bind(comment_id)
-        .execute(pool)
+        .execute(&mut *tx)
         .await?;
+    }
+    tx.commit().await?;
+
+    Ok(ImportOutcome::Imported(imported.len() as u64))
+}
+
+// 获取评论的编辑凭证哈希
+pub async fn get_edit_token_hash(
+    pool: &SqlitePool,
+    comment_id: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    let hash: Option<Option<String>> =
+        sqlx::query_scalar("SELECT edit_token_hash FROM comment WHERE id = ?")
+            .bind(comment_id)
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(hash.flatten())
+}
+
+// 获取评论的编辑历史，按编辑时间正序
+pub async fn get_comment_edits(
+    pool: &SqlitePool,
+    comment_id: &str,
+) -> Result<Vec<CommentEdit>, sqlx::Error> {
+    let edits = sqlx::query_as(
+        "SELECT id, comment_id, content, edit_time FROM comment_edits WHERE comment_id = ? ORDER BY id",
+    )
+    .bind(comment_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(edits)
+}
+
+// 修改评论内容并记录编辑前的内容
+pub async fn update_comment_content(
+    pool: &SqlitePool,
+    comment: &Comment,
+    content: &str,
+    status: &str,
+    edit_time: i64,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("INSERT INTO comment_edits (comment_id, content, edit_time) VALUES (?, ?, ?)")
+        .bind(&comment.id)
+        .bind(&comment.content)
+        .bind(edit_time)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("UPDATE comment SET content = ?, status = ?, edit_time = ? WHERE id = ?")
+        .bind(content)
+        .bind(status)
+        .bind(edit_time)
+        .bind(&comment.id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+// 设置点赞状态，重复点赞或取消未点赞的评论不改变点赞数
+//
+// 返回(最新点赞数, 点赞记录是否有变化)，评论不存在、不属于该应用或未公开时返回None。
+pub async fn set_comment_like(
+    pool: &SqlitePool,
+    app_id: &str,
+    comment_id: &str,
+    liker: &str,
+    liked: bool,
+    now: i64,
+) -> Result<Option<(i64, bool)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let exists: Option<i64> = sqlx::query_scalar(
+        "SELECT like FROM comment WHERE id = ? AND app = ? AND status = 'approved'",
+    )
+    .bind(comment_id)
+    .bind(app_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if exists.is_none() {
+        return Ok(None);
+    }
+
+    let changed = if liked {
+        sqlx::query(
+            "INSERT OR IGNORE INTO comment_likes (comment_id, liker, create_time) VALUES (?, ?, ?)",
+        )
+        .bind(comment_id)
+        .bind(liker)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+    } else {
+        sqlx::query("DELETE FROM comment_likes WHERE comment_id = ? AND liker = ?")
+            .bind(comment_id)
+            .bind(liker)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+    };
+
+    // 历史点赞没有点赞记录，只在记录变化时增减计数以保留原有点赞数
+    if changed > 0 {
+        let delta = if liked { 1 } else { -1 };
+        sqlx::query("UPDATE comment SET like = MAX(like + ?, 0) WHERE id = ?")
+            .bind(delta)
+            .bind(comment_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let like: i64 = sqlx::query_scalar("SELECT like FROM comment WHERE id = ?")
+        .bind(comment_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(Some((like, changed > 0)))
+}
+
+// 获取点赞者在话题下点赞过的评论id
+pub async fn get_liked_comment_ids(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+    liker: &str,
+) -> Result<HashSet<String>, sqlx::Error> {
+    let ids: Vec<String> = sqlx::query_scalar(
+        "SELECT l.comment_id FROM comment_likes l JOIN comment c ON c.id = l.comment_id WHERE l.liker = ? AND c.app = ? AND c.topic = ?",
+    )
+    .bind(liker)
+    .bind(app_id)
+    .bind(topic)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(ids.into_iter().collect())
+}
+
+// 管理员分页查询评论，返回当前页评论和总数
+pub async fn search_comments(
+    pool: &SqlitePool,
+    filter: &CommentFilter,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<Comment>, i64), sqlx::Error> {
+    fn push_filter(builder: &mut QueryBuilder<'_, sqlx::Sqlite>, filter: &CommentFilter) {
+        builder.push(" WHERE 1 = 1");
+        if let Some(app) = &filter.app {
+            builder.push(" AND app = ").push_bind(app.clone());
+        }
+        if let Some(topic) = &filter.topic {
+            builder.push(" AND topic = ").push_bind(topic.clone());
+        }
+        if let Some(status) = &filter.status {
+            builder.push(" AND status = ").push_bind(status.clone());
+        }
+    }
+
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM comment");
+    push_filter(&mut count_builder, filter);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(pool).await?;
+
+    let mut builder = QueryBuilder::new(format!("SELECT {} FROM comment", COMMENT_COLUMNS));
+    push_filter(&mut builder, filter);
+    builder
+        .push(" ORDER BY create_time DESC, id DESC LIMIT ")
+        .push_bind(size)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * size);
+    let comments = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok((comments, total))
+}
+
+// trigram分词的最短关键词长度，更短的关键词改用LIKE扫描
+const FTS_MIN_QUERY_CHARS: usize = 3;
+
+// 全文搜索评论，返回当前页结果和总数
+//
+// 关键词不少于3个字符时使用FTS5索引并按相关度排序，否则退化为LIKE匹配并按时间倒序。
+pub async fn full_text_search(
+    pool: &SqlitePool,
+    search: &CommentSearch,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<CommentSearchHit>, i64), sqlx::Error> {
+    let use_fts = search.query.chars().count() >= FTS_MIN_QUERY_CHARS;
+
+    let push_filter = |builder: &mut QueryBuilder<'_, sqlx::Sqlite>| {
+        if use_fts {
+            // 按短语匹配，关键词中的引号需要转义
+            let phrase = format!("\"{}\"", search.query.replace('"', "\"\""));
+            builder.push(" WHERE comment_fts MATCH ").push_bind(phrase);
+        } else {
+            let pattern = format!(
+                "%{}%",
+                search.query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+            );
+            builder
+                .push(" WHERE comment_fts.content LIKE ")
+                .push_bind(pattern)
+                .push(" ESCAPE '\\'");
+        }
+        if let Some(app) = &search.app {
+            builder.push(" AND c.app = ").push_bind(app.clone());
+        }
+        if let Some(topic) = &search.topic {
+            builder.push(" AND c.topic = ").push_bind(topic.clone());
+        }
+        if let Some(status) = &search.status {
+            builder.push(" AND c.status = ").push_bind(status.clone());
+        }
+        if let Some(from) = search.from {
+            builder.push(" AND c.create_time >= ").push_bind(from);
+        }
+        if let Some(to) = search.to {
+            builder.push(" AND c.create_time < ").push_bind(to);
+        }
+    };
+    let from = " FROM comment_fts JOIN comment c ON c.id = comment_fts.comment_id";
+
+    let mut count_builder = QueryBuilder::new(format!("SELECT COUNT(*){}", from));
+    push_filter(&mut count_builder);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(pool).await?;
+
+    let columns = COMMENT_COLUMNS
+        .split(", ")
+        .map(|column| format!("c.{}", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    // 短关键词的片段由调用方生成
+    let snippet = if use_fts {
+        "snippet(comment_fts, 0, char(2), char(3), '…', 24)"
+    } else {
+        "''"
+    };
+    let mut builder = QueryBuilder::new(format!(
+        "SELECT {}, {} AS snippet{}",
+        columns, snippet, from
+    ));
+    push_filter(&mut builder);
+    builder
+        .push(if use_fts {
+            " ORDER BY rank, c.create_time DESC LIMIT "
+        } else {
+            " ORDER BY c.create_time DESC, c.id DESC LIMIT "
+        })
+        .push_bind(size)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * size);
+    let hits = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok((hits, total))
+}
+
+// 管理员审核可以修改的评论状态，作者删除和举报隐藏的评论不能通过审核恢复
+const MODERATABLE_STATUSES: &str = "('pending', 'approved', 'rejected', 'spam')";
+
+// 审核评论：只修改待审核、已公开、已拒绝和垃圾评论的状态，返回被修改的评论（状态为修改前的值）
+pub async fn moderate_comments(
+    pool: &SqlitePool,
+    ids: &[String],
+    status: &str,
+) -> Result<Vec<Comment>, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let mut builder = QueryBuilder::new(format!(
+        "SELECT {} FROM comment WHERE status IN {} AND id IN (",
+        COMMENT_COLUMNS, MODERATABLE_STATUSES
+    ));
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(") ORDER BY create_time ASC, id ASC");
+    let comments: Vec<Comment> = builder.build_query_as().fetch_all(&mut *tx).await?;
+    if comments.is_empty() {
+        return Ok(comments);
+    }
+
+    let mut builder = QueryBuilder::new("UPDATE comment SET status = ");
+    builder.push_bind(status.to_string());
+    builder.push(" WHERE id IN (");
+    let mut separated = builder.separated(", ");
+    for comment in &comments {
+        separated.push_bind(comment.id.clone());
+    }
+    separated.push_unseparated(")");
+    builder.build().execute(&mut *tx).await?;
+    tx.commit().await?;
+
+    Ok(comments)
+}
+
+// 批量更新评论状态
+pub async fn update_comments_status(
+    pool: &SqlitePool,
+    ids: &[String],
+    status: &str,
+) -> Result<u64, sqlx::Error> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let mut builder = QueryBuilder::new("UPDATE comment SET status = ");
+    builder.push_bind(status.to_string());
+    builder.push(" WHERE id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(")");
+    let result = builder.build().execute(pool).await?;
 
     Ok(result.rows_affected())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    fn comment(id: &str, parent_id: Option<&str>, status: &str, create_time: i64) -> Comment {
+        Comment {
+            id: id.to_string(),
+            app: "blog".to_string(),
+            topic: "/posts/a".to_string(),
+            content: format!("content of {}", id),
+            create_time,
+            user: "alice".to_string(),
+            like: 0,
+            to_user: None,
+            to_content: None,
+            parent_id: parent_id.map(|p| p.to_string()),
+            status: status.to_string(),
+            edit_time: None,
+            pinned_time: None,
+            is_author: false,
+            verified: false,
+        }
+    }
+
+    fn ids(comments: &[Comment]) -> Vec<&str> {
+        comments.iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_thread_listing_loads_only_page_threads() {
+        let pool = init_test_pool().await;
+        for c in [
+            comment("r1", None, STATUS_APPROVED, 1),
+            comment("c1", Some("r1"), STATUS_APPROVED, 2),
+            comment("r2", None, STATUS_DELETED, 3),
+            comment("d2", Some("r2"), STATUS_HIDDEN, 4),
+            comment("c2", Some("d2"), STATUS_APPROVED, 5),
+            comment("r3", None, STATUS_DELETED, 6),
+            comment("r4", None, STATUS_APPROVED, 7),
+            comment("c4", Some("r4"), STATUS_APPROVED, 8),
+        ] {
+            insert_comment(&pool, &c, None).await.unwrap();
+        }
+
+        // 已删除但仍有回复的顶层评论作为占位返回，没有回复的不返回
+        let options = CommentListOptions {
+            sort: CommentSort::Oldest,
+            cursor: None,
+            limit: Some(2),
+            roots_only: true,
+        };
+        let roots = list_comments(&pool, "blog", "/posts/a", &options).await.unwrap();
+        assert_eq!(ids(&roots), vec!["r1", "r2"]);
+        assert_eq!(count_comments(&pool, "blog", "/posts/a", true).await.unwrap(), 3);
+
+        // 只加载本页顶层评论下的回复，包括未公开的中间层
+        let root_ids: Vec<String> = roots.iter().map(|c| c.id.clone()).collect();
+        let replies = get_thread_replies(&pool, &root_ids).await.unwrap();
+        assert_eq!(ids(&replies), vec!["c1", "d2", "c2"]);
+    }
+
+    // 搜索并返回命中的评论id
+    async fn search_ids(pool: &SqlitePool, query: &str) -> Vec<String> {
+        let search = CommentSearch {
+            query: query.to_string(),
+            ..Default::default()
+        };
+        let (hits, total) = full_text_search(pool, &search, 1, 20).await.unwrap();
+        assert_eq!(total, hits.len() as i64);
+        hits.into_iter().map(|h| h.comment.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_full_text_search_stays_in_sync() {
+        let pool = init_test_pool().await;
+        let mut first = comment("c1", None, STATUS_APPROVED, 1);
+        first.content = "今天天气真不错，适合出去走走".to_string();
+        let mut second = comment("c2", None, STATUS_APPROVED, 2);
+        second.content = "他说\"你好\"，100%_确定".to_string();
+        insert_comment(&pool, &first, None).await.unwrap();
+        insert_comment(&pool, &second, None).await.unwrap();
+
+        // 中文子串命中，引号按短语转义
+        assert_eq!(search_ids(&pool, "天气真").await, vec!["c1"]);
+        assert_eq!(search_ids(&pool, "\"你好\"").await, vec!["c2"]);
+        assert!(search_ids(&pool, "天气 走走").await.is_empty());
+        // 短关键词退化为LIKE，通配符按字面匹配
+        assert_eq!(search_ids(&pool, "%_").await, vec!["c2"]);
+        assert!(search_ids(&pool, "_%").await.is_empty());
+
+        // 修改内容后只能搜到新内容
+        update_comment_content(&pool, &first, "明天可能下雨", STATUS_APPROVED, 3)
+            .await
+            .unwrap();
+        assert!(search_ids(&pool, "天气真").await.is_empty());
+        assert_eq!(search_ids(&pool, "可能下雨").await, vec!["c1"]);
+
+        // 删除评论后不再命中
+        sqlx::query("DELETE FROM comment WHERE id = ?")
+            .bind("c1")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(search_ids(&pool, "可能下雨").await.is_empty());
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM comment_fts")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_moderation_skips_deleted_and_hidden() {
+        let pool = init_test_pool().await;
+        for c in [
+            comment("p1", None, STATUS_PENDING, 1),
+            comment("s1", None, STATUS_SPAM, 2),
+            comment("d1", None, STATUS_DELETED, 3),
+            comment("h1", None, STATUS_HIDDEN, 4),
+        ] {
+            insert_comment(&pool, &c, None).await.unwrap();
+        }
+        let all: Vec<String> = ["p1", "s1", "d1", "h1"].iter().map(|s| s.to_string()).collect();
+
+        // 返回被修改的评论及其修改前的状态
+        let changed = moderate_comments(&pool, &all, STATUS_APPROVED).await.unwrap();
+        let previous: Vec<(&str, &str)> = changed
+            .iter()
+            .map(|c| (c.id.as_str(), c.status.as_str()))
+            .collect();
+        assert_eq!(previous, vec![("p1", STATUS_PENDING), ("s1", STATUS_SPAM)]);
+
+        let status = |id: &'static str| {
+            let pool = &pool;
+            async move { get_comment_by_id(pool, id).await.unwrap().unwrap().status }
+        };
+        assert_eq!(status("p1").await, STATUS_APPROVED);
+        assert_eq!(status("d1").await, STATUS_DELETED);
+        assert_eq!(status("h1").await, STATUS_HIDDEN);
+        assert!(
+            moderate_comments(&pool, &["d1".to_string()], STATUS_REJECTED)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recent_contents_exclude_checked_comment() {
+        let pool = init_test_pool().await;
+        for c in [
+            comment("a1", None, STATUS_APPROVED, 10),
+            comment("a2", None, STATUS_APPROVED, 20),
+        ] {
+            insert_comment(&pool, &c, None).await.unwrap();
+        }
+
+        // 修改评论时不和自己的旧内容比较
+        let contents = get_recent_topic_contents(&pool, "blog", "/posts/a", 0, "a1")
+            .await
+            .unwrap();
+        assert_eq!(contents, vec!["content of a2".to_string()]);
+        let contents = get_recent_topic_contents(&pool, "blog", "/posts/a", 15, "new")
+            .await
+            .unwrap();
+        assert_eq!(contents, vec!["content of a2".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        let source = init_test_pool().await;
+        insert_comment(&source, &comment("r1", None, STATUS_APPROVED, 1), None)
+            .await
+            .unwrap();
+        insert_comment(&source, &comment("c1", Some("r1"), STATUS_PENDING, 2), None)
+            .await
+            .unwrap();
+        set_comment_like(&source, "blog", "r1", "ip:x", true, 3)
+            .await
+            .unwrap();
+        let comments = get_app_comments(&source, "blog").await.unwrap();
+        let likes = get_app_likes(&source, "blog").await.unwrap();
+
+        let target = init_test_pool().await;
+        assert_eq!(
+            import_comments(&target, &comments, &likes).await.unwrap(),
+            ImportOutcome::Imported(2)
+        );
+        let imported = get_app_comments(&target, "blog").await.unwrap();
+        assert_eq!(ids(&imported), vec!["r1", "c1"]);
+        assert_eq!(imported[0].like, 1);
+        assert_eq!(imported[1].parent_id.as_deref(), Some("r1"));
+        assert_eq!(get_app_likes(&target, "blog").await.unwrap(), likes);
+
+        // 重复导入全部跳过，点赞不会重复
+        assert_eq!(
+            import_comments(&target, &comments, &likes).await.unwrap(),
+            ImportOutcome::Imported(0)
+        );
+        assert_eq!(get_app_likes(&target, "blog").await.unwrap(), likes);
+
+        // id已属于其他应用时整批不导入
+        let mut other = vec![comment("o1", None, STATUS_APPROVED, 4)];
+        other.extend(get_app_comments(&source, "blog").await.unwrap());
+        for c in other.iter_mut() {
+            c.app = "other".to_string();
+        }
+        assert_eq!(
+            import_comments(&target, &other, &[]).await.unwrap(),
+            ImportOutcome::Conflict("r1".to_string())
+        );
+        assert!(get_app_comments(&target, "other").await.unwrap().is_empty());
+
+        // 数据库层面同样拒绝重复id
+        assert!(
+            insert_comment(&target, &comment("r1", None, STATUS_APPROVED, 4), None)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = CommentCursor {
+            sort: CommentSort::MostLiked,
+            like: 12,
+            create_time: 1_700_000_000,
+            id: "a|b-c".to_string(),
+        };
+        assert_eq!(CommentCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn test_cursor_rejects_garbage() {
+        assert_eq!(CommentCursor::decode("zz"), None);
+        assert_eq!(CommentCursor::decode(&hex::encode("newest|x|1|id")), None);
+        assert_eq!(CommentCursor::decode(&hex::encode("random|0|1|id")), None);
+    }
+}
@@ -0,0 +1,327 @@
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+
+use crate::util::time::{default_timezone, parse_timezone};
+use crate::util::token::hash_token;
+
+// 应用审核模式：先发后审、先审后发
+pub const MODERATION_POST: &str = "post";
+pub const MODERATION_PRE: &str = "pre";
+
+// 应用未配置时可用的表情回应
+pub const DEFAULT_REACTIONS: &[&str] = &["👍", "👎", "😄", "🎉", "😕", "❤️"];
+
+// 评论应用数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CommentApp {
+    pub id: String,
+    pub moderation: String,
+    #[serde(rename = "maxLength")]
+    pub max_length: i64,
+    #[serde(rename = "notifyEmail")]
+    pub notify_email: Option<String>,
+    pub disabled: bool,
+    pub markdown: bool,
+    #[serde(rename = "allowImages")]
+    pub allow_images: bool,
+    pub reactions: Option<Json<Vec<String>>>,
+    #[serde(rename = "verifyIdentity")]
+    pub verify_identity: bool,
+    pub timezone: Option<String>,
+    #[serde(rename = "createTime")]
+    pub create_time: Option<i64>,
+    #[serde(skip)]
+    pub key_hash: Option<String>,
+    #[serde(skip)]
+    pub old_key_hash: Option<String>,
+    #[serde(rename = "oldKeyExpireTime")]
+    pub old_key_expire_time: Option<i64>,
+    #[serde(skip)]
+    pub feed_token_hash: Option<String>,
+}
+
+impl CommentApp {
+    /// 应用可用的表情回应，未配置时使用默认表情
+    pub fn reaction_set(&self) -> Vec<String> {
+        match &self.reactions {
+            Some(reactions) => reactions.0.clone(),
+            None => DEFAULT_REACTIONS.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    /// 应用的时区，未设置或无效时使用默认时区
+    pub fn time_zone(&self) -> Tz {
+        self.timezone
+            .as_deref()
+            .and_then(parse_timezone)
+            .unwrap_or_else(default_timezone)
+    }
+
+    /// 校验订阅凭证，未生成凭证时一律拒绝
+    pub fn accepts_feed_token(&self, token: &str) -> bool {
+        self.feed_token_hash.as_deref() == Some(hash_token(token).as_str())
+    }
+
+    /// 校验key：当前key，或宽限期内的旧key
+    pub fn accepts_key(&self, key: &str, now: i64) -> bool {
+        let hash = hash_token(key);
+        if self.key_hash.as_deref() == Some(hash.as_str()) {
+            return true;
+        }
+        self.old_key_hash.as_deref() == Some(hash.as_str())
+            && self.old_key_expire_time.is_some_and(|t| t > now)
+    }
+}
+
+// 应用设置更新内容，为空的字段保持不变
+#[derive(Debug, Default)]
+pub struct CommentAppSettings {
+    pub moderation: Option<String>,
+    pub max_length: Option<i64>,
+    pub notify_email: Option<Option<String>>,
+    pub disabled: Option<bool>,
+    pub markdown: Option<bool>,
+    pub allow_images: Option<bool>,
+    pub reactions: Option<Option<Vec<String>>>,
+    pub verify_identity: Option<bool>,
+    pub timezone: Option<Option<String>>,
+}
+
+const COMMENT_APP_COLUMNS: &str = "id, moderation, max_length, notify_email, disabled, markdown, allow_images, reactions, verify_identity, timezone, create_time, key_hash, old_key_hash, old_key_expire_time, feed_token_hash";
+
+// 根据id获取评论应用
+pub async fn get_comment_app(
+    pool: &SqlitePool,
+    app_id: &str,
+) -> Result<Option<CommentApp>, sqlx::Error> {
+    let app = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_apps WHERE id = ?",
+        COMMENT_APP_COLUMNS
+    ))
+    .bind(app_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(app)
+}
+
+// 根据app和key获取评论应用，key不匹配或应用已停用时返回None
+pub async fn get_app_by_key(
+    pool: &SqlitePool,
+    app_id: &str,
+    key: &str,
+) -> Result<Option<CommentApp>, sqlx::Error> {
+    let app = get_comment_app(pool, app_id).await?;
+    let now = chrono::Utc::now().timestamp();
+
+    Ok(app.filter(|app| !app.disabled && app.accepts_key(key, now)))
+}
+
+// 获取所有评论应用
+pub async fn get_comment_apps(pool: &SqlitePool) -> Result<Vec<CommentApp>, sqlx::Error> {
+    let apps = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_apps ORDER BY id",
+        COMMENT_APP_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    Ok(apps)
+}
+
+// 创建评论应用，只保存key的哈希
+pub async fn insert_comment_app(
+    pool: &SqlitePool,
+    app: &CommentApp,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO comment_apps (id, key, key_hash, moderation, max_length, notify_email, disabled, markdown, allow_images, reactions, verify_identity, timezone, create_time) VALUES (?, '', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&app.id)
+    .bind(&app.key_hash)
+    .bind(&app.moderation)
+    .bind(app.max_length)
+    .bind(&app.notify_email)
+    .bind(app.disabled)
+    .bind(app.markdown)
+    .bind(app.allow_images)
+    .bind(&app.reactions)
+    .bind(app.verify_identity)
+    .bind(&app.timezone)
+    .bind(app.create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 更新应用设置
+pub async fn update_comment_app_settings(
+    pool: &SqlitePool,
+    app_id: &str,
+    settings: &CommentAppSettings,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE comment_apps SET moderation = COALESCE(?, moderation), max_length = COALESCE(?, max_length), notify_email = CASE WHEN ? THEN ? ELSE notify_email END, disabled = COALESCE(?, disabled), markdown = COALESCE(?, markdown), allow_images = COALESCE(?, allow_images), reactions = CASE WHEN ? THEN ? ELSE reactions END, verify_identity = COALESCE(?, verify_identity), timezone = CASE WHEN ? THEN ? ELSE timezone END WHERE id = ?",
+    )
+    .bind(&settings.moderation)
+    .bind(settings.max_length)
+    .bind(settings.notify_email.is_some())
+    .bind(settings.notify_email.clone().flatten())
+    .bind(settings.disabled)
+    .bind(settings.markdown)
+    .bind(settings.allow_images)
+    .bind(settings.reactions.is_some())
+    .bind(settings.reactions.clone().flatten().map(Json))
+    .bind(settings.verify_identity)
+    .bind(settings.timezone.is_some())
+    .bind(settings.timezone.clone().flatten())
+    .bind(app_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 轮换key：当前key转为旧key，在old_key_expire_time之前仍可使用
+pub async fn rotate_comment_app_key(
+    pool: &SqlitePool,
+    app_id: &str,
+    new_key_hash: &str,
+    old_key_expire_time: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE comment_apps SET old_key_hash = key_hash, old_key_expire_time = ?, key_hash = ? WHERE id = ?",
+    )
+    .bind(old_key_expire_time)
+    .bind(new_key_hash)
+    .bind(app_id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 设置订阅凭证哈希，旧凭证立即失效
+pub async fn set_feed_token_hash(
+    pool: &SqlitePool,
+    app_id: &str,
+    feed_token_hash: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE comment_apps SET feed_token_hash = ? WHERE id = ?")
+        .bind(feed_token_hash)
+        .bind(app_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 将历史遗留的明文key转为哈希并清空明文，返回转换的应用数
+pub async fn hash_plaintext_app_keys(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+    let apps: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, key FROM comment_apps WHERE key_hash IS NULL AND key != ''")
+            .fetch_all(pool)
+            .await?;
+
+    for (id, key) in &apps {
+        sqlx::query("UPDATE comment_apps SET key_hash = ?, key = '' WHERE id = ?")
+            .bind(hash_token(key))
+            .bind(id)
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(apps.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    fn app(key: &str) -> CommentApp {
+        CommentApp {
+            id: "blog".to_string(),
+            moderation: MODERATION_POST.to_string(),
+            max_length: 1023,
+            notify_email: None,
+            disabled: false,
+            markdown: false,
+            allow_images: false,
+            reactions: None,
+            verify_identity: false,
+            timezone: None,
+            create_time: None,
+            key_hash: Some(hash_token(key)),
+            old_key_hash: None,
+            old_key_expire_time: None,
+            feed_token_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_accepts_current_key() {
+        let app = app("new-key");
+        assert!(app.accepts_key("new-key", 0));
+        assert!(!app.accepts_key("other", 0));
+    }
+
+    #[test]
+    fn test_accepts_old_key_within_grace_period() {
+        let mut app = app("new-key");
+        app.old_key_hash = Some(hash_token("old-key"));
+        app.old_key_expire_time = Some(1000);
+        assert!(app.accepts_key("old-key", 999));
+        assert!(!app.accepts_key("old-key", 1000));
+    }
+
+    #[tokio::test]
+    async fn test_hash_plaintext_app_keys() {
+        let pool = init_test_pool().await;
+        sqlx::query("INSERT INTO comment_apps (id, key) VALUES ('legacy', 'plain-key')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_comment_app(&pool, &app("new-key")).await.unwrap();
+        let stored = |id: &'static str| {
+            let pool = &pool;
+            async move {
+                sqlx::query_as::<_, (String, Option<String>)>(
+                    "SELECT key, key_hash FROM comment_apps WHERE id = ?",
+                )
+                .bind(id)
+                .fetch_one(pool)
+                .await
+                .unwrap()
+            }
+        };
+        assert_eq!(stored("legacy").await, ("plain-key".to_string(), None));
+
+        // 只转换明文key，转换后以原key验证且不再保存明文
+        assert_eq!(hash_plaintext_app_keys(&pool).await.unwrap(), 1);
+        assert_eq!(
+            stored("legacy").await,
+            (String::new(), Some(hash_token("plain-key")))
+        );
+        assert!(
+            get_app_by_key(&pool, "legacy", "plain-key")
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert_eq!(
+            stored("blog").await,
+            (String::new(), Some(hash_token("new-key")))
+        );
+
+        // 重复执行不再修改
+        assert_eq!(hash_plaintext_app_keys(&pool).await.unwrap(), 0);
+        assert_eq!(
+            stored("legacy").await,
+            (String::new(), Some(hash_token("plain-key")))
+        );
+    }
+}
@@ -3,6 +3,8 @@ use sqlx::{SqlitePool, migrate::Migrator, sqlite::SqlitePoolOptions};
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::dao::comment_app::hash_plaintext_app_keys;
+
 /// 初始化数据库连接池 + 执行迁移
 pub async fn init_database_pool() -> Result<Arc<SqlitePool>> {
     // 从环境变量读取数据库URL
@@ -10,7 +12,7 @@ pub async fn init_database_pool() -> Result<Arc<SqlitePool>> {
     // 文件不存在时，创建文件
     if !Path::new(&db_file).exists() {
         std::fs::create_dir_all(Path::new(&db_file).parent().unwrap())?;
-        std::fs::File::create(&db_file)?;
+        std::fs::File::create(db_file)?;
     }
 
     let db_url = format!("sqlite://{}", db_file);
@@ -26,9 +28,39 @@ pub async fn init_database_pool() -> Result<Arc<SqlitePool>> {
     if migrations_dir.exists() {
         let migrator = Migrator::new(migrations_dir).await?;
         migrator.run(&pool).await?;
+
+        // 历史明文保存的评论应用key转为哈希
+        let hashed = hash_plaintext_app_keys(&pool).await?;
+        if hashed > 0 {
+            println!("已将 {} 个评论应用key转为哈希保存", hashed);
+        }
     } else {
         eprintln!("⚠️  未找到迁移目录: {}", migrations_dir.display());
     }
 
     Ok(Arc::new(pool))
 }
+
+/// 测试用内存数据库：建立线上初始化迁移中的基础表后执行全部迁移
+#[cfg(test)]
+pub async fn init_test_pool() -> SqlitePool {
+    // 内存数据库只在单个连接内有效
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    for sql in [
+        "CREATE TABLE comment (id TEXT NOT NULL, app TEXT NOT NULL, topic TEXT NOT NULL, content TEXT NOT NULL, create_time INTEGER NOT NULL, user TEXT NOT NULL, like INTEGER NOT NULL DEFAULT 0, to_user TEXT, to_content TEXT)",
+        "CREATE TABLE comment_apps (id TEXT PRIMARY KEY, key TEXT NOT NULL)",
+    ] {
+        sqlx::query(sql).execute(&pool).await.unwrap();
+    }
+    Migrator::new(Path::new("./db/migrations"))
+        .await
+        .unwrap()
+        .run(&pool)
+        .await
+        .unwrap();
+    pool
+}
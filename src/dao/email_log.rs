@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqlitePool};
+
+// 邮件发送记录数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct EmailLog {
+    pub id: i64,
+    pub recipient: String,
+    pub subject: String,
+    pub template: String,
+    #[serde(rename = "apiKey")]
+    pub api_key: Option<String>,
+    pub status: String,
+    #[serde(rename = "smtpResponse")]
+    pub smtp_response: Option<String>,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+}
+
+// 邮件发送记录查询条件
+#[derive(Debug, Default)]
+pub struct EmailLogFilter {
+    pub recipient: Option<String>,
+    pub subject: Option<String>,
+    pub template: Option<String>,
+    pub api_key: Option<String>,
+    pub status: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+// 插入发送记录，返回记录id
+pub async fn insert_email_log(
+    pool: &SqlitePool,
+    recipient: &str,
+    subject: &str,
+    template: &str,
+    api_key: Option<&str>,
+    status: &str,
+    create_time: i64,
+) -> Result<i64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO email_log (recipient, subject, template, api_key, status, create_time, update_time) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(recipient)
+    .bind(subject)
+    .bind(template)
+    .bind(api_key)
+    .bind(status)
+    .bind(create_time)
+    .bind(create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+// 更新发送结果
+pub async fn update_email_log_status(
+    pool: &SqlitePool,
+    id: i64,
+    status: &str,
+    smtp_response: Option<&str>,
+    update_time: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE email_log SET status = ?, smtp_response = ?, update_time = ? WHERE id = ?",
+    )
+    .bind(status)
+    .bind(smtp_response)
+    .bind(update_time)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 拼接查询条件
+fn push_filter(builder: &mut QueryBuilder<'_, Sqlite>, filter: &EmailLogFilter) {
+    builder.push(" WHERE 1 = 1");
+    if let Some(recipient) = &filter.recipient {
+        builder
+            .push(" AND recipient LIKE ")
+            .push_bind(format!("%{}%", recipient));
+    }
+    if let Some(subject) = &filter.subject {
+        builder
+            .push(" AND subject LIKE ")
+            .push_bind(format!("%{}%", subject));
+    }
+    if let Some(template) = &filter.template {
+        builder.push(" AND template = ").push_bind(template.clone());
+    }
+    if let Some(api_key) = &filter.api_key {
+        builder.push(" AND api_key = ").push_bind(api_key.clone());
+    }
+    if let Some(status) = &filter.status {
+        builder.push(" AND status = ").push_bind(status.clone());
+    }
+    if let Some(from) = filter.from {
+        builder.push(" AND create_time >= ").push_bind(from);
+    }
+    if let Some(to) = filter.to {
+        builder.push(" AND create_time <= ").push_bind(to);
+    }
+}
+
+// 分页查询发送记录，返回当前页记录和总数
+pub async fn search_email_logs(
+    pool: &SqlitePool,
+    filter: &EmailLogFilter,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<EmailLog>, i64), sqlx::Error> {
+    let mut count_builder = QueryBuilder::new("SELECT COUNT(*) FROM email_log");
+    push_filter(&mut count_builder, filter);
+    let total: i64 = count_builder.build_query_scalar().fetch_one(pool).await?;
+
+    let mut builder = QueryBuilder::new(
+        "SELECT id, recipient, subject, template, api_key, status, smtp_response, create_time, update_time FROM email_log",
+    );
+    push_filter(&mut builder, filter);
+    builder
+        .push(" ORDER BY id DESC LIMIT ")
+        .push_bind(size)
+        .push(" OFFSET ")
+        .push_bind((page - 1) * size);
+    let logs = builder.build_query_as().fetch_all(pool).await?;
+
+    Ok((logs, total))
+}
+
+// 清理指定时间之前的发送记录
+pub async fn delete_email_logs_before(pool: &SqlitePool, cutoff_time: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM email_log WHERE create_time < ?")
+        .bind(cutoff_time)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,96 @@
+use sqlx::SqlitePool;
+
+// 保存邮箱登录凭证哈希
+pub async fn insert_login_token(
+    pool: &SqlitePool,
+    token_hash: &str,
+    app_id: &str,
+    email: &str,
+    create_time: i64,
+    expire_time: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO comment_login_tokens (token_hash, app, email, create_time, expire_time) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(token_hash)
+    .bind(app_id)
+    .bind(email)
+    .bind(create_time)
+    .bind(expire_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 统计邮箱在指定时间之后申请的登录凭证数，用于限制发送频率
+pub async fn count_recent_login_tokens(
+    pool: &SqlitePool,
+    app_id: &str,
+    email: &str,
+    since: i64,
+) -> Result<i64, sqlx::Error> {
+    let count = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM comment_login_tokens WHERE app = ? AND email = ? AND create_time >= ?",
+    )
+    .bind(app_id)
+    .bind(email)
+    .bind(since)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+// 使用登录凭证并登记已验证的邮箱，返回邮箱；凭证无效、已使用或已过期时返回None
+pub async fn consume_login_token(
+    pool: &SqlitePool,
+    token_hash: &str,
+    app_id: &str,
+    now: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let email: Option<String> = sqlx::query_scalar(
+        "SELECT email FROM comment_login_tokens WHERE token_hash = ? AND app = ? AND used_time IS NULL AND expire_time > ?",
+    )
+    .bind(token_hash)
+    .bind(app_id)
+    .bind(now)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(email) = email else {
+        return Ok(None);
+    };
+
+    sqlx::query("UPDATE comment_login_tokens SET used_time = ? WHERE token_hash = ?")
+        .bind(now)
+        .bind(token_hash)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT OR IGNORE INTO verified_identities (app, email, verify_time) VALUES (?, ?, ?)")
+        .bind(app_id)
+        .bind(&email)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(Some(email))
+}
+
+// 邮箱是否已在应用中通过验证
+pub async fn is_verified_identity(
+    pool: &SqlitePool,
+    app_id: &str,
+    email: &str,
+) -> Result<bool, sqlx::Error> {
+    let verified: Option<i64> =
+        sqlx::query_scalar("SELECT 1 FROM verified_identities WHERE app = ? AND email = ?")
+            .bind(app_id)
+            .bind(email.to_lowercase())
+            .fetch_optional(pool)
+            .await?;
+
+    Ok(verified.is_some())
+}
@@ -1,5 +1,16 @@
 pub mod app;
+pub mod blocklist;
 pub mod blog;
 pub mod clipboard;
 pub mod comment;
+pub mod comment_app;
 pub mod database;
+pub mod email_log;
+pub mod identity;
+pub mod notification;
+pub mod reaction;
+pub mod report;
+pub mod scheduled_email;
+pub mod suppression;
+pub mod topic;
+pub mod webhook;
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+// 待汇总通知数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct PendingNotification {
+    pub id: i64,
+    pub event_type: String,
+    pub subject: String,
+    pub content: String,
+    pub create_time: i64,
+}
+
+// 加入待汇总通知队列
+pub async fn insert_notification(
+    pool: &SqlitePool,
+    event_type: &str,
+    subject: &str,
+    content: &str,
+    create_time: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO notification_queue (event_type, subject, content, create_time) VALUES (?, ?, ?, ?)",
+    )
+    .bind(event_type)
+    .bind(subject)
+    .bind(content)
+    .bind(create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 获取所有待汇总通知
+pub async fn get_pending_notifications(
+    pool: &SqlitePool,
+) -> Result<Vec<PendingNotification>, sqlx::Error> {
+    let notifications = sqlx::query_as(
+        "SELECT id, event_type, subject, content, create_time FROM notification_queue ORDER BY id",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(notifications)
+}
+
+// 删除已汇总发送的通知（id不大于max_id）
+pub async fn delete_notifications_up_to(pool: &SqlitePool, max_id: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM notification_queue WHERE id <= ?")
+        .bind(max_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+// 评论表情回应统计
+#[derive(Debug, FromRow)]
+pub struct ReactionCount {
+    pub comment_id: String,
+    pub reaction: String,
+    pub count: i64,
+}
+
+// 表情回应响应数据结构
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ReactionSummary {
+    pub reaction: String,
+    pub count: i64,
+}
+
+// 切换表情回应：已回应则取消，否则添加
+//
+// 返回切换后是否处于已回应状态，评论不存在、不属于该应用或未公开时返回None。
+pub async fn toggle_reaction(
+    pool: &SqlitePool,
+    app_id: &str,
+    comment_id: &str,
+    reactor: &str,
+    reaction: &str,
+    now: i64,
+) -> Result<Option<bool>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let exists: Option<String> =
+        sqlx::query_scalar("SELECT id FROM comment WHERE id = ? AND app = ? AND status = 'approved'")
+            .bind(comment_id)
+            .bind(app_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if exists.is_none() {
+        return Ok(None);
+    }
+
+    let removed = sqlx::query(
+        "DELETE FROM comment_reactions WHERE comment_id = ? AND reactor = ? AND reaction = ?",
+    )
+    .bind(comment_id)
+    .bind(reactor)
+    .bind(reaction)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    if removed == 0 {
+        sqlx::query(
+            "INSERT INTO comment_reactions (comment_id, reactor, reaction, create_time) VALUES (?, ?, ?, ?)",
+        )
+        .bind(comment_id)
+        .bind(reactor)
+        .bind(reaction)
+        .bind(now)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    Ok(Some(removed == 0))
+}
+
+// 统计话题下每条已公开评论的表情回应数
+pub async fn get_reaction_counts(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+) -> Result<Vec<ReactionCount>, sqlx::Error> {
+    let counts = sqlx::query_as(
+        "SELECT r.comment_id, r.reaction, COUNT(*) AS count FROM comment_reactions r JOIN comment c ON c.id = r.comment_id WHERE c.app = ? AND c.topic = ? AND c.status = 'approved' GROUP BY r.comment_id, r.reaction",
+    )
+    .bind(app_id)
+    .bind(topic)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+// 统计单条评论的表情回应数
+pub async fn get_comment_reaction_counts(
+    pool: &SqlitePool,
+    comment_id: &str,
+) -> Result<Vec<ReactionCount>, sqlx::Error> {
+    let counts = sqlx::query_as(
+        "SELECT comment_id, reaction, COUNT(*) AS count FROM comment_reactions WHERE comment_id = ? GROUP BY reaction",
+    )
+    .bind(comment_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(counts)
+}
+
+// 获取访客在话题下已公开评论的表情回应，返回(评论id, 表情)
+pub async fn get_visitor_reactions(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+    reactor: &str,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+    let reactions = sqlx::query_as(
+        "SELECT r.comment_id, r.reaction FROM comment_reactions r JOIN comment c ON c.id = r.comment_id WHERE r.reactor = ? AND c.app = ? AND c.topic = ? AND c.status = 'approved'",
+    )
+    .bind(reactor)
+    .bind(app_id)
+    .bind(topic)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(reactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    #[tokio::test]
+    async fn test_reaction_counts_only_approved_comments() {
+        let pool = init_test_pool().await;
+        for id in ["a1", "a2"] {
+            sqlx::query(
+                "INSERT INTO comment (id, app, topic, content, create_time, user, status) VALUES (?, 'blog', '/posts/a', '', 1, 'alice', 'approved')",
+            )
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+            toggle_reaction(&pool, "blog", id, "ip:a", "👍", 1)
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE comment SET status = 'hidden' WHERE id = 'a2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let counts = get_reaction_counts(&pool, "blog", "/posts/a")
+            .await
+            .unwrap();
+        let ids: Vec<&str> = counts.iter().map(|c| c.comment_id.as_str()).collect();
+        assert_eq!(ids, vec!["a1"]);
+        let mine = get_visitor_reactions(&pool, "blog", "/posts/a", "ip:a")
+            .await
+            .unwrap();
+        assert_eq!(mine, vec![("a1".to_string(), "👍".to_string())]);
+    }
+}
@@ -0,0 +1,326 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, SqlitePool};
+
+use super::comment::{COMMENT_COLUMNS, Comment, STATUS_APPROVED, STATUS_HIDDEN};
+
+// 评论举报数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CommentReport {
+    #[serde(rename = "commentId")]
+    pub comment_id: String,
+    pub reporter: String,
+    pub reason: String,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+}
+
+// 被举报的评论及待处理举报统计
+#[derive(Debug, FromRow, Serialize)]
+pub struct ReportedComment {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub comment: Comment,
+    #[serde(rename = "reportCount")]
+    pub report_count: i64,
+    #[serde(rename = "lastReportTime")]
+    pub last_report_time: i64,
+    #[sqlx(skip)]
+    pub reports: Vec<CommentReport>,
+}
+
+// 举报结果
+#[derive(Debug)]
+pub struct ReportOutcome {
+    /// 是否新增了举报（同一举报人重复举报不计）
+    pub inserted: bool,
+    /// 待处理举报的举报人数（每个举报人只记一次）
+    pub report_count: i64,
+    /// 本次举报是否使评论被自动隐藏
+    pub hidden: bool,
+}
+
+// 举报评论，待处理举报的举报人数达到threshold时自动隐藏评论
+//
+// 评论不存在、不属于该应用或未公开时返回None。
+pub async fn insert_report(
+    pool: &SqlitePool,
+    app_id: &str,
+    report: &CommentReport,
+    threshold: i64,
+) -> Result<Option<ReportOutcome>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let exists: Option<String> =
+        sqlx::query_scalar("SELECT id FROM comment WHERE id = ? AND app = ? AND status = ?")
+            .bind(&report.comment_id)
+            .bind(app_id)
+            .bind(STATUS_APPROVED)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if exists.is_none() {
+        return Ok(None);
+    }
+
+    let inserted = sqlx::query(
+        "INSERT OR IGNORE INTO comment_reports (comment_id, reporter, reason, create_time) VALUES (?, ?, ?, ?)",
+    )
+    .bind(&report.comment_id)
+    .bind(&report.reporter)
+    .bind(&report.reason)
+    .bind(report.create_time)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    let report_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM comment_reports WHERE comment_id = ? AND resolved_time IS NULL",
+    )
+    .bind(&report.comment_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let mut hidden = false;
+    if inserted && report_count >= threshold {
+        hidden = sqlx::query("UPDATE comment SET status = ? WHERE id = ? AND status = ?")
+            .bind(STATUS_HIDDEN)
+            .bind(&report.comment_id)
+            .bind(STATUS_APPROVED)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+            > 0;
+    }
+    tx.commit().await?;
+
+    Ok(Some(ReportOutcome {
+        inserted,
+        report_count,
+        hidden,
+    }))
+}
+
+// 分页查询有待处理举报的评论，按最近举报时间倒序
+pub async fn list_reported_comments(
+    pool: &SqlitePool,
+    app_id: Option<&str>,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<ReportedComment>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM comment WHERE id IN (SELECT comment_id FROM comment_reports WHERE resolved_time IS NULL) AND (? IS NULL OR app = ?)",
+    )
+    .bind(app_id)
+    .bind(app_id)
+    .fetch_one(pool)
+    .await?;
+
+    let comments = sqlx::query_as(&format!(
+        "SELECT {}, \
+         (SELECT COUNT(*) FROM comment_reports r WHERE r.comment_id = comment.id AND r.resolved_time IS NULL) AS report_count, \
+         (SELECT MAX(r.create_time) FROM comment_reports r WHERE r.comment_id = comment.id AND r.resolved_time IS NULL) AS last_report_time \
+         FROM comment WHERE id IN (SELECT comment_id FROM comment_reports WHERE resolved_time IS NULL) AND (? IS NULL OR app = ?) \
+         ORDER BY last_report_time DESC, id DESC LIMIT ? OFFSET ?",
+        COMMENT_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(app_id)
+    .bind(size)
+    .bind((page - 1) * size)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((comments, total))
+}
+
+// 获取多条评论的待处理举报，按举报时间正序
+pub async fn get_pending_reports(
+    pool: &SqlitePool,
+    comment_ids: &[String],
+) -> Result<Vec<CommentReport>, sqlx::Error> {
+    if comment_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT comment_id, reporter, reason, create_time FROM comment_reports WHERE resolved_time IS NULL AND comment_id IN (",
+    );
+    let mut separated = builder.separated(", ");
+    for id in comment_ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(") ORDER BY create_time ASC");
+
+    let reports = builder.build_query_as().fetch_all(pool).await?;
+    Ok(reports)
+}
+
+// 处理评论的全部待处理举报并更新评论状态
+//
+// status为approved时只恢复被自动隐藏的评论；返回处理的举报数。
+pub async fn resolve_reports(
+    pool: &SqlitePool,
+    comment_id: &str,
+    status: &str,
+    now: i64,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let resolved = sqlx::query(
+        "UPDATE comment_reports SET resolved_time = ? WHERE comment_id = ? AND resolved_time IS NULL",
+    )
+    .bind(now)
+    .bind(comment_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    if resolved > 0 {
+        if status == STATUS_APPROVED {
+            sqlx::query("UPDATE comment SET status = ? WHERE id = ? AND status = ?")
+                .bind(STATUS_APPROVED)
+                .bind(comment_id)
+                .bind(STATUS_HIDDEN)
+                .execute(&mut *tx)
+                .await?;
+        } else {
+            sqlx::query("UPDATE comment SET status = ? WHERE id = ?")
+                .bind(status)
+                .bind(comment_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::comment::{STATUS_REJECTED, get_comment_by_id, insert_comment};
+    use crate::dao::database::init_test_pool;
+
+    async fn setup() -> SqlitePool {
+        let pool = init_test_pool().await;
+        let comment = Comment {
+            id: "c1".to_string(),
+            app: "blog".to_string(),
+            topic: "/posts/a".to_string(),
+            content: "hello".to_string(),
+            create_time: 1,
+            user: "alice".to_string(),
+            like: 0,
+            to_user: None,
+            to_content: None,
+            parent_id: None,
+            status: STATUS_APPROVED.to_string(),
+            edit_time: None,
+            pinned_time: None,
+            is_author: false,
+            verified: false,
+        };
+        insert_comment(&pool, &comment, None).await.unwrap();
+        pool
+    }
+
+    async fn report(pool: &SqlitePool, reporter: &str) -> Option<ReportOutcome> {
+        let report = CommentReport {
+            comment_id: "c1".to_string(),
+            reporter: reporter.to_string(),
+            reason: "spam".to_string(),
+            create_time: 2,
+        };
+        insert_report(pool, "blog", &report, 2).await.unwrap()
+    }
+
+    async fn status(pool: &SqlitePool) -> String {
+        get_comment_by_id(pool, "c1").await.unwrap().unwrap().status
+    }
+
+    #[tokio::test]
+    async fn test_reports_hide_comment_at_distinct_reporter_threshold() {
+        let pool = setup().await;
+
+        let first = report(&pool, "ip:a").await.unwrap();
+        assert!(first.inserted);
+        assert_eq!(first.report_count, 1);
+
+        // 同一举报人重复举报不计数
+        let repeated = report(&pool, "ip:a").await.unwrap();
+        assert!(!repeated.inserted);
+        assert_eq!(repeated.report_count, 1);
+        assert!(!repeated.hidden);
+        assert_eq!(status(&pool).await, STATUS_APPROVED);
+
+        let second = report(&pool, "u:bob@example.com").await.unwrap();
+        assert_eq!(second.report_count, 2);
+        assert!(second.hidden);
+        assert_eq!(status(&pool).await, STATUS_HIDDEN);
+        // 管理员队列的举报数与隐藏阈值使用同一计数
+        let (reported, _) = list_reported_comments(&pool, Some("blog"), 1, 10)
+            .await
+            .unwrap();
+        assert_eq!(reported[0].report_count, second.report_count);
+
+        // 已隐藏的评论不能再举报
+        assert!(report(&pool, "ip:c").await.is_none());
+        assert!(
+            insert_report(
+                &pool,
+                "other",
+                &CommentReport {
+                    comment_id: "c1".to_string(),
+                    reporter: "ip:d".to_string(),
+                    reason: "spam".to_string(),
+                    create_time: 3,
+                },
+                2
+            )
+            .await
+            .unwrap()
+            .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_reports_dismiss_and_remove() {
+        let pool = setup().await;
+        report(&pool, "ip:a").await.unwrap();
+        report(&pool, "ip:b").await.unwrap();
+        assert_eq!(status(&pool).await, STATUS_HIDDEN);
+
+        // 驳回举报恢复被隐藏的评论
+        assert_eq!(
+            resolve_reports(&pool, "c1", STATUS_APPROVED, 3)
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(status(&pool).await, STATUS_APPROVED);
+        assert_eq!(
+            list_reported_comments(&pool, Some("blog"), 1, 10)
+                .await
+                .unwrap()
+                .1,
+            0
+        );
+        assert_eq!(
+            resolve_reports(&pool, "c1", STATUS_APPROVED, 4)
+                .await
+                .unwrap(),
+            0
+        );
+
+        // 新的举报可以删除评论
+        report(&pool, "ip:c").await.unwrap();
+        assert_eq!(
+            resolve_reports(&pool, "c1", STATUS_REJECTED, 5)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(status(&pool).await, STATUS_REJECTED);
+    }
+}
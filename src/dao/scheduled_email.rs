@@ -0,0 +1,170 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+// 定时邮件数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct ScheduledEmail {
+    #[serde(rename = "_id")]
+    pub id: String,
+    #[serde(skip_serializing)]
+    pub api_key: String,
+    #[serde(rename = "to")]
+    pub recipient: String,
+    pub subject: String,
+    pub content: String,
+    #[serde(rename = "sendAt")]
+    pub send_at: i64,
+    pub status: String,
+    pub error: Option<String>,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+}
+
+// 插入定时邮件
+pub async fn insert_scheduled_email(
+    pool: &SqlitePool,
+    email: &ScheduledEmail,
+) -> Result<String, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO scheduled_email (id, api_key, recipient, subject, content, send_at, status, error, create_time, update_time) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&email.id)
+    .bind(&email.api_key)
+    .bind(&email.recipient)
+    .bind(&email.subject)
+    .bind(&email.content)
+    .bind(email.send_at)
+    .bind(&email.status)
+    .bind(&email.error)
+    .bind(email.create_time)
+    .bind(email.update_time)
+    .execute(pool)
+    .await?;
+
+    Ok(email.id.clone())
+}
+
+// 根据密钥指纹获取定时邮件列表
+pub async fn get_scheduled_emails_by_key(
+    pool: &SqlitePool,
+    api_key: &str,
+) -> Result<Vec<ScheduledEmail>, sqlx::Error> {
+    let emails = sqlx::query_as(
+        "SELECT id, api_key, recipient, subject, content, send_at, status, error, create_time, update_time FROM scheduled_email WHERE api_key = ? ORDER BY send_at DESC",
+    )
+    .bind(api_key)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(emails)
+}
+
+// 获取已到发送时间的定时邮件
+pub async fn get_due_scheduled_emails(
+    pool: &SqlitePool,
+    now: i64,
+) -> Result<Vec<ScheduledEmail>, sqlx::Error> {
+    let emails = sqlx::query_as(
+        "SELECT id, api_key, recipient, subject, content, send_at, status, error, create_time, update_time FROM scheduled_email WHERE status = 'pending' AND send_at <= ? ORDER BY send_at",
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(emails)
+}
+
+// 更新定时邮件状态
+pub async fn update_scheduled_email_status(
+    pool: &SqlitePool,
+    id: &str,
+    status: &str,
+    error: Option<&str>,
+    update_time: i64,
+) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query("UPDATE scheduled_email SET status = ?, error = ?, update_time = ? WHERE id = ?")
+            .bind(status)
+            .bind(error)
+            .bind(update_time)
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 取消尚未发送的定时邮件，只能取消本密钥创建的邮件
+pub async fn cancel_scheduled_email(
+    pool: &SqlitePool,
+    id: &str,
+    api_key: &str,
+    update_time: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE scheduled_email SET status = 'cancelled', update_time = ? WHERE id = ? AND api_key = ? AND status = 'pending'",
+    )
+    .bind(update_time)
+    .bind(id)
+    .bind(api_key)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    fn email(id: &str, api_key: &str, send_at: i64) -> ScheduledEmail {
+        ScheduledEmail {
+            id: id.to_string(),
+            api_key: api_key.to_string(),
+            recipient: "user@example.com".to_string(),
+            subject: "subject".to_string(),
+            content: format!("content of {}", id),
+            send_at,
+            status: "pending".to_string(),
+            error: None,
+            create_time: 0,
+            update_time: 0,
+        }
+    }
+
+    fn ids(emails: &[ScheduledEmail]) -> Vec<&str> {
+        emails.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_schedule_list_and_cancel() {
+        let pool = init_test_pool().await;
+        for (id, api_key, send_at) in [("e1", "k1", 100), ("e2", "k1", 200), ("e3", "k2", 50)] {
+            insert_scheduled_email(&pool, &email(id, api_key, send_at))
+                .await
+                .unwrap();
+        }
+
+        // 只列出本密钥创建的邮件，按发送时间倒序
+        let listed = get_scheduled_emails_by_key(&pool, "k1").await.unwrap();
+        assert_eq!(ids(&listed), vec!["e2", "e1"]);
+
+        // 不能取消其他密钥的邮件，已取消的不能再取消
+        let cancel = |api_key: &'static str| cancel_scheduled_email(&pool, "e1", api_key, 1);
+        assert_eq!(cancel("k2").await.unwrap(), 0);
+        assert_eq!(cancel("k1").await.unwrap(), 1);
+        assert_eq!(cancel("k1").await.unwrap(), 0);
+
+        // 已取消和未到时间的邮件不会被发送
+        let due = get_due_scheduled_emails(&pool, 150).await.unwrap();
+        assert_eq!(ids(&due), vec!["e3"]);
+        update_scheduled_email_status(&pool, "e3", "sent", None, 150)
+            .await
+            .unwrap();
+        let due = get_due_scheduled_emails(&pool, 300).await.unwrap();
+        assert_eq!(ids(&due), vec!["e2"]);
+    }
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+// 屏蔽名单数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct Suppression {
+    pub email: String,
+    pub reason: String,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+}
+
+// 检查邮箱是否在屏蔽名单中
+pub async fn is_suppressed(pool: &SqlitePool, email: &str) -> Result<bool, sqlx::Error> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM email_suppression WHERE email = ?",
+    )
+    .bind(email.to_lowercase())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count > 0)
+}
+
+// 加入屏蔽名单，已存在时保留原记录
+pub async fn add_suppression(
+    pool: &SqlitePool,
+    email: &str,
+    reason: &str,
+    create_time: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO email_suppression (email, reason, create_time) VALUES (?, ?, ?) ON CONFLICT(email) DO NOTHING",
+    )
+    .bind(email.to_lowercase())
+    .bind(reason)
+    .bind(create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 从屏蔽名单移除
+pub async fn remove_suppression(pool: &SqlitePool, email: &str) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM email_suppression WHERE email = ?")
+        .bind(email.to_lowercase())
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 分页查询屏蔽名单，返回当前页记录和总数
+pub async fn get_suppressions(
+    pool: &SqlitePool,
+    email: Option<&str>,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<Suppression>, i64), sqlx::Error> {
+    let pattern = format!("%{}%", email.unwrap_or_default().to_lowercase());
+
+    let total = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM email_suppression WHERE email LIKE ?",
+    )
+    .bind(&pattern)
+    .fetch_one(pool)
+    .await?;
+
+    let suppressions = sqlx::query_as(
+        "SELECT email, reason, create_time FROM email_suppression WHERE email LIKE ? ORDER BY create_time DESC LIMIT ? OFFSET ?",
+    )
+    .bind(&pattern)
+    .bind(size)
+    .bind((page - 1) * size)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((suppressions, total))
+}
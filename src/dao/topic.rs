@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, SqlitePool};
+
+// 评论话题数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize, Clone)]
+pub struct CommentTopic {
+    pub app: String,
+    pub topic: String,
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub locked: bool,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+}
+
+// 修改话题设置，None表示不修改；title和url为Some(None)时清除
+#[derive(Debug, Default)]
+pub struct TopicSettings {
+    pub topic: Option<String>, // 重命名话题，话题下的评论一起迁移
+    pub title: Option<Option<String>>,
+    pub url: Option<Option<String>>,
+    pub locked: Option<bool>,
+}
+
+// 查询话题时的字段列表
+const TOPIC_COLUMNS: &str = "app, topic, title, url, locked, create_time";
+
+// 获取话题
+pub async fn get_topic(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+) -> Result<Option<CommentTopic>, sqlx::Error> {
+    let topic = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_topics WHERE app = ? AND topic = ?",
+        TOPIC_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(topic)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(topic)
+}
+
+// 批量获取应用下的话题
+pub async fn get_topics_by_names(
+    pool: &SqlitePool,
+    app_id: &str,
+    topics: &[String],
+) -> Result<Vec<CommentTopic>, sqlx::Error> {
+    if topics.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut builder = QueryBuilder::new(format!(
+        "SELECT {} FROM comment_topics WHERE app = ",
+        TOPIC_COLUMNS
+    ));
+    builder.push_bind(app_id);
+    builder.push(" AND topic IN (");
+    let mut separated = builder.separated(", ");
+    for topic in topics {
+        separated.push_bind(topic);
+    }
+    separated.push_unseparated(")");
+
+    let topics = builder.build_query_as().fetch_all(pool).await?;
+    Ok(topics)
+}
+
+// 分页查询应用下的话题，按创建时间倒序
+pub async fn list_topics(
+    pool: &SqlitePool,
+    app_id: &str,
+    locked: Option<bool>,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<CommentTopic>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM comment_topics WHERE app = ? AND (? IS NULL OR locked = ?)",
+    )
+    .bind(app_id)
+    .bind(locked)
+    .bind(locked)
+    .fetch_one(pool)
+    .await?;
+
+    let topics = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_topics WHERE app = ? AND (? IS NULL OR locked = ?) ORDER BY create_time DESC, topic LIMIT ? OFFSET ?",
+        TOPIC_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(locked)
+    .bind(locked)
+    .bind(size)
+    .bind((page - 1) * size)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((topics, total))
+}
+
+// 修改话题设置，返回影响行数；重命名时在同一事务中修改话题登记和话题下的评论
+pub async fn update_topic(
+    pool: &SqlitePool,
+    app_id: &str,
+    topic: &str,
+    settings: &TopicSettings,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE comment_topics SET topic = COALESCE(?, topic), title = CASE WHEN ? THEN ? ELSE title END, url = CASE WHEN ? THEN ? ELSE url END, locked = COALESCE(?, locked) WHERE app = ? AND topic = ?",
+    )
+    .bind(settings.topic.as_deref())
+    .bind(settings.title.is_some())
+    .bind(settings.title.clone().flatten())
+    .bind(settings.url.is_some())
+    .bind(settings.url.clone().flatten())
+    .bind(settings.locked)
+    .bind(app_id)
+    .bind(topic)
+    .execute(&mut *tx)
+    .await?;
+
+    if result.rows_affected() > 0
+        && let Some(new_topic) = &settings.topic
+    {
+        sqlx::query("UPDATE comment SET topic = ? WHERE app = ? AND topic = ?")
+            .bind(new_topic)
+            .bind(app_id)
+            .bind(topic)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    async fn comment_topics(pool: &SqlitePool) -> Vec<(String, String)> {
+        sqlx::query_as("SELECT id, topic FROM comment ORDER BY id")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rename_topic_moves_comments() {
+        let pool = init_test_pool().await;
+        for (id, app, topic) in [
+            ("c1", "blog", "/old"),
+            ("c2", "blog", "/old"),
+            ("c3", "docs", "/old"),
+        ] {
+            sqlx::query(
+                "INSERT INTO comment (id, app, topic, content, create_time, user) VALUES (?, ?, ?, '', 1, 'alice')",
+            )
+            .bind(id)
+            .bind(app)
+            .bind(topic)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let settings = TopicSettings {
+            title: Some(Some("标题".to_string())),
+            ..Default::default()
+        };
+        update_topic(&pool, "blog", "/old", &settings)
+            .await
+            .unwrap();
+
+        let rename = TopicSettings {
+            topic: Some("/new".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            update_topic(&pool, "blog", "/old", &rename).await.unwrap(),
+            1
+        );
+
+        // 话题登记保留原有设置，其他应用的同名话题不受影响
+        assert!(get_topic(&pool, "blog", "/old").await.unwrap().is_none());
+        let renamed = get_topic(&pool, "blog", "/new").await.unwrap().unwrap();
+        assert_eq!(renamed.title.as_deref(), Some("标题"));
+        let expected: Vec<(String, String)> = [("c1", "/new"), ("c2", "/new"), ("c3", "/old")]
+            .iter()
+            .map(|(id, topic)| (id.to_string(), topic.to_string()))
+            .collect();
+        assert_eq!(comment_topics(&pool).await, expected);
+
+        // 不存在的话题不修改评论
+        assert_eq!(
+            update_topic(&pool, "blog", "/old", &rename).await.unwrap(),
+            0
+        );
+        assert_eq!(comment_topics(&pool).await, expected);
+    }
+}
@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use sqlx::{FromRow, SqlitePool};
+
+// 投递状态：待投递（含等待重试）、成功、重试次数用尽后失败
+pub const DELIVERY_PENDING: &str = "pending";
+pub const DELIVERY_SUCCESS: &str = "success";
+pub const DELIVERY_FAILED: &str = "failed";
+
+// Webhook订阅数据结构
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct CommentWebhook {
+    pub id: String,
+    pub app: String,
+    pub url: String,
+    #[serde(skip)]
+    pub secret: String,
+    pub events: Json<Vec<String>>,
+    pub enabled: bool,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+}
+
+// Webhook设置更新内容，为空的字段保持不变
+#[derive(Debug, Default)]
+pub struct WebhookSettings {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub enabled: Option<bool>,
+}
+
+// Webhook投递记录
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    #[serde(rename = "webhookId")]
+    pub webhook_id: String,
+    pub event: String,
+    pub payload: String,
+    pub status: String,
+    pub attempts: i64,
+    #[serde(rename = "nextAttemptTime")]
+    pub next_attempt_time: i64,
+    #[serde(rename = "responseStatus")]
+    pub response_status: Option<i64>,
+    pub error: Option<String>,
+    #[serde(rename = "createTime")]
+    pub create_time: i64,
+    #[serde(rename = "updateTime")]
+    pub update_time: i64,
+}
+
+// 一次投递的结果
+#[derive(Debug)]
+pub struct DeliveryResult<'a> {
+    pub status: &'a str,
+    pub attempts: i64,
+    pub next_attempt_time: i64,
+    pub response_status: Option<i64>,
+    pub error: Option<String>,
+}
+
+const WEBHOOK_COLUMNS: &str = "id, app, url, secret, events, enabled, create_time";
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event, payload, status, attempts, next_attempt_time, response_status, error, create_time, update_time";
+
+// 创建Webhook订阅
+pub async fn insert_webhook(pool: &SqlitePool, webhook: &CommentWebhook) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO comment_webhooks (id, app, url, secret, events, enabled, create_time) VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&webhook.id)
+    .bind(&webhook.app)
+    .bind(&webhook.url)
+    .bind(&webhook.secret)
+    .bind(&webhook.events)
+    .bind(webhook.enabled)
+    .bind(webhook.create_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 根据id获取Webhook订阅
+pub async fn get_webhook(pool: &SqlitePool, id: &str) -> Result<Option<CommentWebhook>, sqlx::Error> {
+    let webhook = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_webhooks WHERE id = ?",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(webhook)
+}
+
+// 获取Webhook订阅列表，app为空时返回全部
+pub async fn list_webhooks(
+    pool: &SqlitePool,
+    app_id: Option<&str>,
+) -> Result<Vec<CommentWebhook>, sqlx::Error> {
+    let webhooks = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_webhooks WHERE (? IS NULL OR app = ?) ORDER BY create_time, id",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(app_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(webhooks)
+}
+
+// 获取应用中订阅了指定事件的已启用Webhook
+pub async fn get_subscribed_webhooks(
+    pool: &SqlitePool,
+    app_id: &str,
+    event: &str,
+) -> Result<Vec<CommentWebhook>, sqlx::Error> {
+    let webhooks = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_webhooks WHERE app = ? AND enabled = 1 AND EXISTS (SELECT 1 FROM json_each(events) WHERE value = ?)",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(app_id)
+    .bind(event)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(webhooks)
+}
+
+// 修改Webhook设置
+pub async fn update_webhook(
+    pool: &SqlitePool,
+    id: &str,
+    settings: &WebhookSettings,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE comment_webhooks SET url = COALESCE(?, url), events = COALESCE(?, events), enabled = COALESCE(?, enabled) WHERE id = ?",
+    )
+    .bind(&settings.url)
+    .bind(settings.events.clone().map(Json))
+    .bind(settings.enabled)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// 删除Webhook订阅及其投递记录
+pub async fn delete_webhook(pool: &SqlitePool, id: &str) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM comment_webhook_deliveries WHERE webhook_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    let result = sqlx::query("DELETE FROM comment_webhooks WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(result.rows_affected())
+}
+
+// 插入待投递记录
+pub async fn insert_delivery(pool: &SqlitePool, delivery: &WebhookDelivery) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO comment_webhook_deliveries (id, webhook_id, event, payload, status, attempts, next_attempt_time, create_time, update_time) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&delivery.id)
+    .bind(&delivery.webhook_id)
+    .bind(&delivery.event)
+    .bind(&delivery.payload)
+    .bind(&delivery.status)
+    .bind(delivery.attempts)
+    .bind(delivery.next_attempt_time)
+    .bind(delivery.create_time)
+    .bind(delivery.update_time)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 获取已到投递时间的记录
+pub async fn get_due_deliveries(
+    pool: &SqlitePool,
+    now: i64,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    let deliveries = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_webhook_deliveries WHERE status = ? AND next_attempt_time <= ? ORDER BY next_attempt_time LIMIT ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(DELIVERY_PENDING)
+    .bind(now)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(deliveries)
+}
+
+// 领取待投递记录：推迟下次投递时间到lease_until，避免同时运行的任务重复投递
+//
+// 返回是否领取成功。
+pub async fn claim_delivery(
+    pool: &SqlitePool,
+    id: &str,
+    now: i64,
+    lease_until: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE comment_webhook_deliveries SET next_attempt_time = ? WHERE id = ? AND status = ? AND next_attempt_time <= ?",
+    )
+    .bind(lease_until)
+    .bind(id)
+    .bind(DELIVERY_PENDING)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// 记录投递结果
+pub async fn finish_delivery(
+    pool: &SqlitePool,
+    id: &str,
+    result: &DeliveryResult<'_>,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE comment_webhook_deliveries SET status = ?, attempts = ?, next_attempt_time = ?, response_status = ?, error = ?, update_time = ? WHERE id = ?",
+    )
+    .bind(result.status)
+    .bind(result.attempts)
+    .bind(result.next_attempt_time)
+    .bind(result.response_status)
+    .bind(&result.error)
+    .bind(now)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 分页查询Webhook的投递记录，按创建时间倒序
+pub async fn list_deliveries(
+    pool: &SqlitePool,
+    webhook_id: &str,
+    status: Option<&str>,
+    page: i64,
+    size: i64,
+) -> Result<(Vec<WebhookDelivery>, i64), sqlx::Error> {
+    let total: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM comment_webhook_deliveries WHERE webhook_id = ? AND (? IS NULL OR status = ?)",
+    )
+    .bind(webhook_id)
+    .bind(status)
+    .bind(status)
+    .fetch_one(pool)
+    .await?;
+
+    let deliveries = sqlx::query_as(&format!(
+        "SELECT {} FROM comment_webhook_deliveries WHERE webhook_id = ? AND (? IS NULL OR status = ?) ORDER BY create_time DESC, id LIMIT ? OFFSET ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(webhook_id)
+    .bind(status)
+    .bind(status)
+    .bind(size)
+    .bind((page - 1) * size)
+    .fetch_all(pool)
+    .await?;
+
+    Ok((deliveries, total))
+}
@@ -1,6 +1,13 @@
 use crate::controller::blog;
 use crate::controller::clipboard;
 use crate::controller::comment;
+use crate::controller::comment_admin;
+use crate::controller::comment_app;
+use crate::controller::comment_feed;
+use crate::controller::comment_identity;
+use crate::controller::comment_topic;
+use crate::controller::comment_transfer;
+use crate::controller::comment_webhook;
 use crate::controller::config;
 use crate::controller::coze;
 use crate::controller::email;
@@ -8,16 +15,17 @@ use crate::controller::state;
 use crate::controller::wechat;
 use crate::dao::database::init_database_pool;
 use axum::{
-    routing::{get, post},
-    Router,
+    extract::{DefaultBodyLimit, Request},
+    routing::{delete, get, post, put},
+    Router, ServiceExt,
 };
 use tower::ServiceBuilder;
-use tower::make::Shared;
 use tower_http::normalize_path::NormalizePathLayer;
 use tower_http::trace::TraceLayer;
 use dotenv::dotenv;
 use sqlx::SqlitePool;
 use std::env;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::{catch_panic::CatchPanicLayer, services::ServeDir};
 
@@ -28,10 +36,25 @@ mod util;
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
-    println!("🚀 服务器启动中，v{}", env!("CARGO_PKG_VERSION"));
     // 加载.env文件
     dotenv().ok();
 
+    // 诊断命令：发送测试邮件并打印签名后的邮件原文
+    let args: Vec<String> = env::args().collect();
+    if args.get(1).map(String::as_str) == Some("send-test-email") {
+        let Some(to) = args.get(2) else {
+            eprintln!("用法: rust_backend send-test-email <收件人邮箱>");
+            std::process::exit(2);
+        };
+        if let Err(e) = util::email::send_test_email(to).await {
+            eprintln!("❌ 测试邮件发送失败: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    println!("🚀 服务器启动中，v{}", env!("CARGO_PKG_VERSION"));
+
     // 初始化日志
     tracing_subscriber::fmt::init();
 
@@ -58,11 +81,116 @@ async fn main() -> std::io::Result<()> {
     let api_routes: Router<Arc<SqlitePool>> = Router::default()
         .route("/", get(state::state))
         .route("/email", post(email::send_email_handler))
+        .route("/email/scheduled", get(email::get_scheduled_emails))
+        .route(
+            "/email/scheduled/:id",
+            delete(email::cancel_scheduled_email_handler),
+        )
+        .route(
+            "/email/unsubscribe",
+            get(email::unsubscribe_page).post(email::unsubscribe),
+        )
+        .route("/admin/email/logs", get(email::get_email_logs))
+        .route(
+            "/admin/email/suppressions",
+            get(email::get_suppression_list).post(email::add_suppression_handler),
+        )
+        .route(
+            "/admin/email/suppressions/:email",
+            delete(email::remove_suppression_handler),
+        )
         .route("/wechat/apps", get(wechat::get_apps))
         .route(
             "/comment",
             get(comment::get_comments).post(comment::post_comment),
         )
+        .route("/comment/counts", post(comment::get_comment_counts))
+        .route("/comment/feed", get(comment_feed::get_feed))
+        .route(
+            "/comment/identity/request",
+            post(comment_identity::request_login),
+        )
+        .route(
+            "/comment/identity/verify",
+            post(comment_identity::verify_login),
+        )
+        .route(
+            "/comment/:id",
+            put(comment::edit_comment).delete(comment::delete_comment),
+        )
+        .route("/comment/:id/reactions", post(comment::react_comment))
+        .route("/comment/:id/report", post(comment::report_comment))
+        .route("/admin/comments", get(comment_admin::get_comments))
+        .route("/admin/comments/search", get(comment_admin::search))
+        .route("/admin/comments/bulk", post(comment_admin::bulk_moderate))
+        .route(
+            "/admin/comments/export",
+            get(comment_transfer::export_comments),
+        )
+        .route(
+            "/admin/comments/import",
+            post(comment_transfer::import)
+                .layer(DefaultBodyLimit::max(comment_transfer::MAX_IMPORT_BYTES)),
+        )
+        .route(
+            "/admin/comments/:id/approve",
+            post(comment_admin::approve_comment),
+        )
+        .route(
+            "/admin/comments/:id/reject",
+            post(comment_admin::reject_comment),
+        )
+        .route("/admin/comments/reports", get(comment_admin::get_reports))
+        .route(
+            "/admin/comments/:id/reports/resolve",
+            post(comment_admin::resolve_comment_reports),
+        )
+        .route("/admin/comments/:id/pin", post(comment_admin::pin_comment))
+        .route(
+            "/admin/comments/:id/unpin",
+            post(comment_admin::unpin_comment),
+        )
+        .route(
+            "/admin/comments/:id/edits",
+            get(comment_admin::get_edit_history),
+        )
+        .route(
+            "/admin/comment-apps",
+            get(comment_app::get_apps).post(comment_app::create_app),
+        )
+        .route("/admin/comment-apps/:id", put(comment_app::update_app))
+        .route(
+            "/admin/comment-apps/:id/rotate",
+            post(comment_app::rotate_key),
+        )
+        .route(
+            "/admin/comment-apps/:id/feed-token",
+            post(comment_app::generate_feed_token),
+        )
+        .route(
+            "/admin/comment-topics",
+            get(comment_topic::get_topics).put(comment_topic::update),
+        )
+        .route(
+            "/admin/comment-webhooks",
+            get(comment_webhook::get_webhooks).post(comment_webhook::create),
+        )
+        .route(
+            "/admin/comment-webhooks/:id",
+            put(comment_webhook::update).delete(comment_webhook::delete),
+        )
+        .route(
+            "/admin/comment-webhooks/:id/deliveries",
+            get(comment_webhook::get_deliveries),
+        )
+        .route(
+            "/admin/comment-blocklist",
+            get(comment_admin::get_blocklist).post(comment_admin::add_block_rule),
+        )
+        .route(
+            "/admin/comment-blocklist/:id",
+            delete(comment_admin::remove_block_rule),
+        )
         .route("/clipboard/:id", get(clipboard::get_by_id))
         .route(
             "/clipboard/openid/:openid",
@@ -102,7 +230,12 @@ async fn main() -> std::io::Result<()> {
     let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", port)).await?;
     println!("✅ 端口 {} 绑定成功，服务器开始运行", port);
 
-    axum::serve(listener, Shared::new(app)).await?;
+    // 保留连接地址，未配置信任的代理头时以此识别访客
+    axum::serve(
+        listener,
+        ServiceExt::<Request>::into_make_service_with_connect_info::<SocketAddr>(app),
+    )
+    .await?;
     println!("🛑 服务器已停止");
 
     Ok(())
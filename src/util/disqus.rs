@@ -0,0 +1,208 @@
+use chrono::DateTime;
+use lazy_static::lazy_static;
+use regex::Regex;
+use roxmltree::{Document, Node};
+use std::collections::HashMap;
+
+use crate::dao::comment::{Comment, STATUS_APPROVED, STATUS_DELETED, STATUS_SPAM};
+
+// Disqus导出文件中内部属性的命名空间
+const DSQ_NS: &str = "http://disqus.com/disqus-internals";
+
+lazy_static! {
+    static ref BREAK_REGEX: Regex = Regex::new(r"(?i)<br\s*/?>|</p>\s*<p>").unwrap();
+    static ref TAG_REGEX: Regex = Regex::new(r"<[^>]*>").unwrap();
+}
+
+// 子元素的文本内容
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|n| n.has_tag_name(name))
+        .and_then(|n| n.text())
+        .map(|t| t.trim())
+}
+
+// dsq:id属性
+fn dsq_id<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((DSQ_NS, "id"))
+}
+
+/// 将Disqus评论的HTML转为纯文本：换行标签转为换行，其余标签去除
+pub fn html_to_text(html: &str) -> String {
+    let text = BREAK_REGEX.replace_all(html, "\n");
+    let text = TAG_REGEX.replace_all(&text, "");
+    text.replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&")
+        .trim()
+        .to_string()
+}
+
+/// 讨论串地址对应的话题：取URL路径，去掉查询参数和末尾的斜杠
+pub fn thread_topic(link: &str) -> String {
+    match reqwest::Url::parse(link) {
+        Ok(url) => {
+            let path = url.path().trim_end_matches('/');
+            if path.is_empty() {
+                "/".to_string()
+            } else {
+                path.to_string()
+            }
+        }
+        Err(_) => link.to_string(),
+    }
+}
+
+/// 解析Disqus导出的XML为评论列表
+///
+/// 评论id为 `disqus-` 加Disqus的id，重复导入时可据此去重；用户优先取邮箱，没有邮箱时取昵称。
+/// 已删除和垃圾评论保留原状态，回复会带上被回复评论的用户和内容。
+pub fn parse_disqus(xml: &str, app: &str) -> Result<Vec<Comment>, String> {
+    let doc = Document::parse(xml).map_err(|e| format!("invalid xml: {}", e))?;
+    let root = doc.root_element();
+
+    // 讨论串id到话题的映射
+    let mut topics: HashMap<&str, String> = HashMap::new();
+    for thread in root.children().filter(|n| n.has_tag_name("thread")) {
+        if let (Some(id), Some(link)) = (dsq_id(thread), child_text(thread, "link")) {
+            topics.insert(id, thread_topic(link));
+        }
+    }
+
+    let mut comments = Vec::new();
+    for post in root.children().filter(|n| n.has_tag_name("post")) {
+        let Some(id) = dsq_id(post) else {
+            continue;
+        };
+        let thread_id = post
+            .children()
+            .find(|n| n.has_tag_name("thread"))
+            .and_then(dsq_id);
+        let Some(topic) = thread_id.and_then(|t| topics.get(t)) else {
+            return Err(format!("post {} has no known thread", id));
+        };
+
+        let created_at = child_text(post, "createdAt").unwrap_or_default();
+        let create_time = DateTime::parse_from_rfc3339(created_at)
+            .map_err(|_| format!("post {} has invalid createdAt", id))?
+            .timestamp();
+
+        let author = post.children().find(|n| n.has_tag_name("author"));
+        let user = author
+            .and_then(|a| child_text(a, "email").filter(|e| !e.is_empty()))
+            .or_else(|| author.and_then(|a| child_text(a, "name")))
+            .unwrap_or("匿名")
+            .to_string();
+
+        let status = if child_text(post, "isDeleted") == Some("true") {
+            STATUS_DELETED
+        } else if child_text(post, "isSpam") == Some("true") {
+            STATUS_SPAM
+        } else {
+            STATUS_APPROVED
+        };
+
+        let parent_id = post
+            .children()
+            .find(|n| n.has_tag_name("parent"))
+            .and_then(dsq_id)
+            .map(|p| format!("disqus-{}", p));
+
+        comments.push(Comment {
+            id: format!("disqus-{}", id),
+            app: app.to_string(),
+            topic: topic.clone(),
+            content: html_to_text(child_text(post, "message").unwrap_or_default()),
+            create_time,
+            user,
+            like: 0,
+            to_user: None,
+            to_content: None,
+            parent_id,
+            status: status.to_string(),
+            edit_time: None,
+            pinned_time: None,
+            is_author: false,
+            verified: false,
+        });
+    }
+
+    // 回复带上被回复评论的用户和内容
+    let parents: HashMap<String, (String, String)> = comments
+        .iter()
+        .map(|c| (c.id.clone(), (c.user.clone(), c.content.clone())))
+        .collect();
+    for comment in &mut comments {
+        if let Some((user, content)) = comment.parent_id.as_ref().and_then(|p| parents.get(p)) {
+            comment.to_user = Some(user.clone());
+            comment.to_content = Some(content.clone());
+        }
+    }
+
+    Ok(comments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<disqus xmlns="http://disqus.com" xmlns:dsq="http://disqus.com/disqus-internals">
+  <thread dsq:id="100">
+    <link>https://wycode.cn/posts/hello/?utm=1</link>
+    <title>Hello</title>
+  </thread>
+  <post dsq:id="1">
+    <message><![CDATA[<p>First &amp; best<br>line two</p>]]></message>
+    <createdAt>2020-01-02T03:04:05Z</createdAt>
+    <isDeleted>false</isDeleted>
+    <isSpam>false</isSpam>
+    <author><email>a@qq.com</email><name>A</name></author>
+    <thread dsq:id="100"/>
+  </post>
+  <post dsq:id="2">
+    <message><![CDATA[<p>Reply</p>]]></message>
+    <createdAt>2020-01-03T00:00:00Z</createdAt>
+    <isDeleted>false</isDeleted>
+    <isSpam>true</isSpam>
+    <author><name>B</name></author>
+    <thread dsq:id="100"/>
+    <parent dsq:id="1"/>
+  </post>
+</disqus>"#;
+
+    #[test]
+    fn test_parse_disqus() {
+        let comments = parse_disqus(EXPORT, "blog").unwrap();
+        assert_eq!(comments.len(), 2);
+
+        let first = &comments[0];
+        assert_eq!(first.id, "disqus-1");
+        assert_eq!(first.topic, "/posts/hello");
+        assert_eq!(first.content, "First & best\nline two");
+        assert_eq!(first.user, "a@qq.com");
+        assert_eq!(first.create_time, 1_577_934_245);
+        assert_eq!(first.status, STATUS_APPROVED);
+
+        let reply = &comments[1];
+        assert_eq!(reply.user, "B");
+        assert_eq!(reply.status, STATUS_SPAM);
+        assert_eq!(reply.parent_id.as_deref(), Some("disqus-1"));
+        assert_eq!(reply.to_user.as_deref(), Some("a@qq.com"));
+    }
+
+    #[test]
+    fn test_parse_disqus_rejects_invalid_xml() {
+        assert!(parse_disqus("<disqus>", "blog").is_err());
+    }
+
+    #[test]
+    fn test_thread_topic() {
+        assert_eq!(thread_topic("https://wycode.cn/"), "/");
+        assert_eq!(thread_topic("https://wycode.cn/a/b#c"), "/a/b");
+    }
+}
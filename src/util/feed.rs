@@ -0,0 +1,106 @@
+use chrono::{SecondsFormat, TimeZone, Utc};
+
+/// Atom订阅中的一条记录
+pub struct FeedEntry {
+    /// 稳定的记录标识（IRI）
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub author: String,
+    pub content: String,
+    /// 最后更新时间（秒）
+    pub updated: i64,
+}
+
+// 转义XML特殊字符
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0不允许的控制字符直接丢弃
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// RFC 3339格式时间
+fn rfc3339(ts: i64) -> String {
+    Utc.timestamp_opt(ts, 0)
+        .single()
+        .unwrap_or_default()
+        .to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// 生成Atom订阅文档，feed_id同时作为self链接，updated取最新记录时间
+pub fn build_atom_feed(feed_id: &str, title: &str, entries: &[FeedEntry]) -> String {
+    let updated = entries
+        .iter()
+        .map(|e| e.updated)
+        .max()
+        .unwrap_or_else(|| Utc::now().timestamp());
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape(feed_id)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape(title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", rfc3339(updated)));
+    xml.push_str(&format!(
+        "  <link rel=\"self\" href=\"{}\"/>\n",
+        escape(feed_id)
+    ));
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape(&entry.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape(&entry.title)));
+        xml.push_str(&format!(
+            "    <link rel=\"alternate\" href=\"{}\"/>\n",
+            escape(&entry.link)
+        ));
+        xml.push_str(&format!("    <updated>{}</updated>\n", rfc3339(entry.updated)));
+        xml.push_str(&format!(
+            "    <author><name>{}</name></author>\n",
+            escape(&entry.author)
+        ));
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}</content>\n",
+            escape(&entry.content)
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_atom_feed() {
+        let entries = vec![FeedEntry {
+            id: "https://wycode.cn/post#comment-1".to_string(),
+            title: "a***@qq.com 评论了 /post".to_string(),
+            link: "https://wycode.cn/post".to_string(),
+            author: "a***@qq.com".to_string(),
+            content: "<b>Tom & Jerry</b>".to_string(),
+            updated: 1_700_000_000,
+        }];
+        let xml = build_atom_feed("https://wycode.cn/api/v1/comment/feed?a=blog", "评论", &entries);
+        assert!(xml.contains("<updated>2023-11-14T22:13:20Z</updated>"));
+        assert!(xml.contains("<id>https://wycode.cn/post#comment-1</id>"));
+        assert!(xml.contains("&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"));
+        assert!(xml.contains("href=\"https://wycode.cn/api/v1/comment/feed?a=blog\""));
+    }
+
+    #[test]
+    fn test_escape_drops_control_chars() {
+        assert_eq!(escape("a\u{0}b\n\"'"), "ab\n&quot;&apos;");
+    }
+}
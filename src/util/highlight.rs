@@ -0,0 +1,93 @@
+// 搜索命中的起止标记，使用控制字符以免与评论内容冲突
+pub const MARK_START: char = '\u{2}';
+pub const MARK_END: char = '\u{3}';
+
+/// 将带命中标记的片段转为HTML：内容转义，命中部分用 `<mark>` 包裹
+pub fn to_html(raw: &str) -> String {
+    let mut html = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            MARK_START => html.push_str("<mark>"),
+            MARK_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+/// 截取关键词附近的内容并标记命中（忽略ASCII大小写），radius为关键词前后保留的字符数
+///
+/// 用于FTS无法处理的短关键词；未命中时返回开头部分。
+pub fn excerpt(content: &str, query: &str, radius: usize) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let needle: Vec<char> = query.chars().map(|c| c.to_ascii_lowercase()).collect();
+    if needle.is_empty() || needle.len() > chars.len() {
+        return chars.iter().take(radius * 2).collect();
+    }
+
+    let matches = |i: usize| {
+        needle
+            .iter()
+            .enumerate()
+            .all(|(j, n)| chars[i + j].to_ascii_lowercase() == *n)
+    };
+    let Some(first) = (0..=chars.len() - needle.len()).find(|&i| matches(i)) else {
+        return chars.iter().take(radius * 2).collect();
+    };
+
+    let start = first.saturating_sub(radius);
+    let end = (first + needle.len() + radius).min(chars.len());
+    let mut out = String::new();
+    if start > 0 {
+        out.push('…');
+    }
+    let mut i = start;
+    while i < end {
+        if i + needle.len() <= end && matches(i) {
+            out.push(MARK_START);
+            out.extend(&chars[i..i + needle.len()]);
+            out.push(MARK_END);
+            i += needle.len();
+        } else {
+            out.push(chars[i]);
+            i += 1;
+        }
+    }
+    if end < chars.len() {
+        out.push('…');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html_escapes_and_marks() {
+        let raw = format!("<b>{}评论{}</b>", MARK_START, MARK_END);
+        assert_eq!(to_html(&raw), "&lt;b&gt;<mark>评论</mark>&lt;/b&gt;");
+    }
+
+    #[test]
+    fn test_excerpt_marks_all_hits_near_first() {
+        let raw = excerpt("这是一条测试评论，评论很好", "评论", 3);
+        assert_eq!(to_html(&raw), "…条测试<mark>评论</mark>，<mark>评论</mark>…");
+    }
+
+    #[test]
+    fn test_excerpt_ignores_ascii_case() {
+        let raw = excerpt("Hello Rust", "rust", 10);
+        assert_eq!(to_html(&raw), "Hello <mark>Rust</mark>");
+    }
+
+    #[test]
+    fn test_excerpt_without_hit_returns_head() {
+        assert_eq!(excerpt("abcdef", "xyz", 2), "abcd");
+    }
+}
@@ -0,0 +1,74 @@
+use ammonia::Builder;
+use pulldown_cmark::{Event, Options, Parser, html};
+use std::collections::HashSet;
+
+// 允许的HTML标签：段落、强调、代码、引用、列表、链接
+const ALLOWED_TAGS: &[&str] = &[
+    "p", "br", "hr", "em", "strong", "del", "code", "pre", "blockquote", "ul", "ol", "li", "a",
+];
+
+/// 将评论内容按Markdown渲染为经过清洗的HTML
+///
+/// 原始HTML按文本转义；链接只允许http、https和mailto，并添加rel=nofollow；
+/// 图片只在allow_images为true时保留。
+pub fn render(content: &str, allow_images: bool) -> String {
+    let parser = Parser::new_ext(content, Options::ENABLE_STRIKETHROUGH).map(|event| match event {
+        // 不信任评论中的原始HTML
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        event => event,
+    });
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+
+    let mut tags: HashSet<&str> = ALLOWED_TAGS.iter().copied().collect();
+    if allow_images {
+        tags.insert("img");
+    }
+
+    Builder::default()
+        .tags(tags)
+        .tag_attributes(
+            [("a", ["href"].into()), ("img", ["src", "alt", "title"].into())].into(),
+        )
+        .generic_attributes(HashSet::new())
+        .url_schemes(["http", "https", "mailto"].into())
+        .link_rel(Some("nofollow noopener noreferrer"))
+        .clean(&unsafe_html)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_basic_markdown() {
+        let html = render("**bold** _em_ `code`", false);
+        assert_eq!(
+            html.trim(),
+            "<p><strong>bold</strong> <em>em</em> <code>code</code></p>"
+        );
+    }
+
+    #[test]
+    fn test_render_links_get_nofollow() {
+        let html = render("[site](https://wycode.cn)", false);
+        assert!(html.contains(r#"href="https://wycode.cn""#));
+        assert!(html.contains(r#"rel="nofollow noopener noreferrer""#));
+    }
+
+    #[test]
+    fn test_render_strips_scripts_and_unsafe_links() {
+        let html = render("<script>alert(1)</script>\n\n[x](javascript:alert(1))", false);
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("&lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_render_images_only_when_allowed() {
+        let markdown = "![logo](https://wycode.cn/logo.png)";
+        assert!(!render(markdown, false).contains("<img"));
+        assert!(render(markdown, true).contains(r#"<img src="https://wycode.cn/logo.png""#));
+    }
+}
@@ -1,2 +1,12 @@
+pub mod disqus;
 pub mod email;
+pub mod feed;
+pub mod highlight;
+pub mod markdown;
+pub mod notification;
+pub mod session;
+pub mod spam;
+pub mod time;
+pub mod token;
 pub mod uuid;
+pub mod webhook;
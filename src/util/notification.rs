@@ -0,0 +1,285 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use std::env;
+use std::time::Duration;
+
+use crate::dao::comment::{Comment, STATUS_APPROVED, get_comment_by_id};
+use crate::dao::topic::{CommentTopic, get_topic};
+use crate::dao::notification::{
+    delete_notifications_up_to, get_pending_notifications, insert_notification,
+};
+use crate::util::email::{EmailConfig, hide_email, is_email, send_email};
+use crate::util::time::{TimeFormat, default_timezone};
+
+/// 管理员通知事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotificationEvent {
+    /// 新评论
+    NewComment,
+    /// 评论被举报
+    CommentReported,
+    /// 剪贴板新用户
+    NewClipboardUser,
+    /// 后台任务失败
+    JobFailed,
+}
+
+impl NotificationEvent {
+    /// 事件类型标识，用于 `NOTIFY_DIGEST_EVENTS` 配置和数据库存储
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::NewComment => "comment",
+            NotificationEvent::CommentReported => "comment_report",
+            NotificationEvent::NewClipboardUser => "clipboard_user",
+            NotificationEvent::JobFailed => "job_failed",
+        }
+    }
+
+    /// 该事件是否配置为汇总发送
+    ///
+    /// `NOTIFY_DIGEST_EVENTS` 为逗号分隔的事件类型列表，`all` 表示全部汇总，未设置时全部立即发送。
+    pub fn is_digest(&self) -> bool {
+        let events = env::var("NOTIFY_DIGEST_EVENTS").unwrap_or_default();
+        events
+            .split(',')
+            .map(|s| s.trim())
+            .any(|s| s == "all" || s == self.as_str())
+    }
+}
+
+// 事件类型的中文名称
+fn event_label(event_type: &str) -> &str {
+    match event_type {
+        "comment" => "新评论",
+        "comment_report" => "评论举报",
+        "clipboard_user" => "剪贴板新用户",
+        "job_failed" => "任务失败",
+        other => other,
+    }
+}
+
+/// 汇总邮件发送间隔，读取 `NOTIFY_DIGEST_INTERVAL_MINUTES`，默认60分钟
+pub fn digest_interval() -> Duration {
+    let minutes = env::var("NOTIFY_DIGEST_INTERVAL_MINUTES")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|m| *m > 0)
+        .unwrap_or(60);
+    Duration::from_secs(minutes * 60)
+}
+
+/// 发送管理员通知，按事件类型的配置立即发送或加入汇总队列
+pub async fn notify(
+    pool: &SqlitePool,
+    event: NotificationEvent,
+    subject: String,
+    content: String,
+) -> Result<(), String> {
+    if event.is_digest() {
+        insert_notification(
+            pool,
+            event.as_str(),
+            &subject,
+            &content,
+            Utc::now().timestamp(),
+        )
+        .await
+        .map_err(|e| format!("error queueing notification: {:?}", e))?;
+        return Ok(());
+    }
+
+    send_email(
+        pool,
+        EmailConfig::new(Some(subject), content, None).with_template("notification"),
+    )
+    .await
+}
+
+/// 将队列中的通知合并为一封汇总邮件发送，发送成功后清空已发送的条目
+pub async fn send_digest(pool: &SqlitePool) -> Result<usize, String> {
+    let notifications = get_pending_notifications(pool)
+        .await
+        .map_err(|e| format!("error loading notifications: {:?}", e))?;
+
+    let Some(max_id) = notifications.iter().map(|n| n.id).max() else {
+        return Ok(0);
+    };
+
+    let mut content = format!("共 {} 条通知\n", notifications.len());
+    for n in &notifications {
+        let time = TimeFormat::new(default_timezone(), false, 0).format(n.create_time);
+        content.push_str(&format!(
+            "\n==============================\n[{}] {} {}\n{}\n",
+            event_label(&n.event_type),
+            time,
+            n.subject,
+            n.content
+        ));
+    }
+
+    send_email(
+        pool,
+        EmailConfig::new(
+            Some(format!("【Rust】通知汇总（{} 条）", notifications.len())),
+            content,
+            None,
+        )
+        .with_template("digest"),
+    )
+    .await?;
+
+    delete_notifications_up_to(pool, max_id)
+        .await
+        .map_err(|e| format!("error deleting notifications: {:?}", e))?;
+
+    Ok(notifications.len())
+}
+
+/// 站点地址，读取 `SITE_URL`，默认 https://wycode.cn
+pub fn site_url() -> String {
+    env::var("SITE_URL").unwrap_or("https://wycode.cn".to_string())
+}
+
+/// 话题页面地址：话题本身是完整URL时直接使用，否则拼接到站点地址之后
+pub fn topic_url(site_url: &str, topic: &str) -> String {
+    if topic.starts_with("http://") || topic.starts_with("https://") {
+        return topic.to_string();
+    }
+    format!(
+        "{}/{}",
+        site_url.trim_end_matches('/'),
+        topic.trim_start_matches('/')
+    )
+}
+
+/// 话题的显示标题和页面地址，优先使用话题登记的标题和地址
+pub fn topic_link(topic: &str, meta: Option<&CommentTopic>) -> (String, String) {
+    topic_link_at(&site_url(), topic, meta)
+}
+
+// 按传入的站点地址获取话题的显示标题和页面地址
+fn topic_link_at(site_url: &str, topic: &str, meta: Option<&CommentTopic>) -> (String, String) {
+    let title = meta
+        .and_then(|m| m.title.clone())
+        .unwrap_or_else(|| topic.to_string());
+    let url = meta
+        .and_then(|m| m.url.clone())
+        .unwrap_or_else(|| topic_url(site_url, topic));
+    (title, url)
+}
+
+/// 通知被回复的评论作者
+///
+/// 只在被回复评论的用户名是邮箱地址且回复已公开时发送；自己回复自己不通知。
+/// 退订和屏蔽名单由 `send_email` 统一处理。
+pub async fn notify_reply(pool: &SqlitePool, reply: &Comment) -> Result<(), String> {
+    let Some(parent_id) = &reply.parent_id else {
+        return Ok(());
+    };
+    if reply.status != STATUS_APPROVED {
+        return Ok(());
+    }
+
+    let parent = match get_comment_by_id(pool, parent_id).await {
+        Ok(Some(parent)) if parent.status == STATUS_APPROVED => parent,
+        Ok(_) => return Ok(()),
+        Err(e) => return Err(format!("error loading parent comment: {:?}", e)),
+    };
+    let to = parent.user.trim();
+    if !is_email(to) || to.eq_ignore_ascii_case(reply.user.trim()) {
+        return Ok(());
+    }
+
+    let meta = match get_topic(pool, &reply.app, &reply.topic).await {
+        Ok(meta) => meta,
+        Err(e) => return Err(format!("error loading topic: {:?}", e)),
+    };
+    let (title, url) = topic_link(&reply.topic, meta.as_ref());
+
+    let subject = format!("你在 {} 的评论收到了新回复", title);
+    let content = format!(
+        "{} 回复了你的评论：\n\n{}\n\n你的评论：\n{}\n\n查看回复：{}",
+        hide_email(&reply.user),
+        reply.content,
+        parent.content,
+        url
+    );
+
+    send_email(
+        pool,
+        EmailConfig::new(Some(subject), content, Some(to.to_string())).with_template("reply"),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::database::init_test_pool;
+
+    #[test]
+    fn test_topic_url() {
+        let site = "https://wycode.cn/";
+        assert_eq!(
+            topic_url(site, "https://example.com/post/1"),
+            "https://example.com/post/1"
+        );
+        assert_eq!(
+            topic_url(site, "/posts/hello"),
+            "https://wycode.cn/posts/hello"
+        );
+        assert_eq!(
+            topic_url(site, "posts/hello"),
+            "https://wycode.cn/posts/hello"
+        );
+    }
+
+    #[test]
+    fn test_topic_link_prefers_registry() {
+        let mut meta = CommentTopic {
+            app: "blog".to_string(),
+            topic: "/posts/hello".to_string(),
+            title: Some("你好".to_string()),
+            url: None,
+            locked: false,
+            create_time: 0,
+        };
+        let site = "https://wycode.cn";
+        assert_eq!(
+            topic_link_at(site, "/posts/hello", Some(&meta)),
+            ("你好".to_string(), "https://wycode.cn/posts/hello".to_string())
+        );
+        meta.url = Some("https://example.com/hello".to_string());
+        assert_eq!(
+            topic_link_at(site, "/posts/hello", Some(&meta)).1,
+            "https://example.com/hello"
+        );
+        assert_eq!(topic_link_at(site, "/posts/hello", None).0, "/posts/hello");
+    }
+
+    #[tokio::test]
+    async fn test_send_digest_merges_queue() {
+        let pool = init_test_pool().await;
+        assert_eq!(send_digest(&pool).await, Ok(0));
+
+        let event = NotificationEvent::NewComment.as_str();
+        for (subject, content) in [("新评论 /a", "digest 1"), ("新评论 /b", "digest 2")] {
+            insert_notification(&pool, event, subject, content, 1)
+                .await
+                .unwrap();
+        }
+        assert_eq!(send_digest(&pool).await, Ok(2));
+
+        // 只发送一封汇总邮件，队列被清空
+        let logs: Vec<(String, String)> = sqlx::query_as("SELECT subject, template FROM email_log")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            logs,
+            vec![("【Rust】通知汇总（2 条）".to_string(), "digest".to_string())]
+        );
+        assert!(get_pending_notifications(&pool).await.unwrap().is_empty());
+        assert_eq!(send_digest(&pool).await, Ok(0));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::util::token;
+
+/// 评论者的邮箱登录会话
+#[derive(Debug, PartialEq, Eq)]
+pub struct CommentSession {
+    pub app: String,
+    /// 已验证的邮箱（小写）
+    pub email: String,
+    /// 过期时间（秒）
+    pub expire_time: i64,
+}
+
+// 会话签名的数据，邮箱放在最后以免其中的冒号影响解析
+fn session_data(app: &str, email: &str, expire_time: i64) -> String {
+    format!("comment-session:{}:{}:{}", expire_time, app, email)
+}
+
+/// 签发会话令牌：会话数据的十六进制编码加HMAC签名，服务端无需保存
+pub fn sign_session(session: &CommentSession) -> String {
+    let data = session_data(&session.app, &session.email, session.expire_time);
+    format!("{}.{}", hex::encode(&data), token::sign(&data))
+}
+
+/// 校验会话令牌，签名无效或已过期时返回None
+pub fn verify_session(session_token: &str, now: i64) -> Option<CommentSession> {
+    let (encoded, signature) = session_token.split_once('.')?;
+    let data = String::from_utf8(hex::decode(encoded).ok()?).ok()?;
+    if !token::verify(&data, signature) {
+        return None;
+    }
+
+    let mut parts = data.splitn(4, ':');
+    if parts.next() != Some("comment-session") {
+        return None;
+    }
+    let expire_time = parts.next()?.parse::<i64>().ok()?;
+    let app = parts.next()?.to_string();
+    let email = parts.next()?.to_string();
+    if expire_time <= now {
+        return None;
+    }
+
+    Some(CommentSession {
+        app,
+        email,
+        expire_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session() -> CommentSession {
+        CommentSession {
+            app: "blog".to_string(),
+            email: "a@qq.com".to_string(),
+            expire_time: 2_000,
+        }
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let session_token = sign_session(&session());
+        assert_eq!(verify_session(&session_token, 1_000), Some(session()));
+        assert_eq!(verify_session(&session_token, 2_000), None);
+    }
+
+    #[test]
+    fn test_verify_session_rejects_tampered_token() {
+        let session_token = sign_session(&session());
+        let (_, signature) = session_token.split_once('.').unwrap();
+        let forged = format!(
+            "{}.{}",
+            hex::encode(session_data("blog", "b@qq.com", 2_000)),
+            signature
+        );
+        assert_eq!(verify_session(&forged, 1_000), None);
+        assert_eq!(verify_session("not-a-token", 1_000), None);
+    }
+}
@@ -0,0 +1,247 @@
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+use std::env;
+
+use crate::dao::blocklist::BlockRule;
+
+lazy_static! {
+    static ref LINK_REGEX: Regex = Regex::new(r"(?i)(https?://|www\.)\S+").unwrap();
+    static ref WHITESPACE_REGEX: Regex = Regex::new(r"\s+").unwrap();
+}
+
+/// 垃圾评论检测的输入
+pub struct SpamContext<'a> {
+    pub content: &'a str,
+    pub user: &'a str,
+    /// 蜜罐字段，正常用户看不到，机器人会填写
+    pub honeypot: Option<&'a str>,
+    /// 同一话题下的近期评论内容，用于重复检测
+    pub recent_contents: &'a [String],
+    pub block_rules: &'a [BlockRule],
+}
+
+/// 单个过滤器的命中结果
+#[derive(Debug)]
+pub struct SpamHit {
+    pub filter: &'static str,
+    pub score: i32,
+    pub reason: String,
+}
+
+/// 过滤链的综合结果
+#[derive(Debug, Default)]
+pub struct SpamVerdict {
+    pub score: i32,
+    pub hits: Vec<SpamHit>,
+}
+
+impl SpamVerdict {
+    /// 总分达到阈值（环境变量 `COMMENT_SPAM_THRESHOLD`，默认50）即判为垃圾评论
+    pub fn is_spam(&self) -> bool {
+        let threshold = env::var("COMMENT_SPAM_THRESHOLD")
+            .ok()
+            .and_then(|s| s.parse::<i32>().ok())
+            .unwrap_or(50);
+        self.score >= threshold
+    }
+
+    /// 命中原因摘要
+    pub fn summary(&self) -> String {
+        self.hits
+            .iter()
+            .map(|hit| format!("{}(+{}): {}", hit.filter, hit.score, hit.reason))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// 垃圾评论过滤器，未命中返回None
+pub trait SpamFilter: Send + Sync {
+    fn check(&self, ctx: &SpamContext) -> Option<SpamHit>;
+}
+
+/// 蜜罐字段被填写
+pub struct HoneypotFilter;
+
+impl SpamFilter for HoneypotFilter {
+    fn check(&self, ctx: &SpamContext) -> Option<SpamHit> {
+        ctx.honeypot.filter(|v| !v.trim().is_empty()).map(|_| SpamHit {
+            filter: "honeypot",
+            score: 100,
+            reason: "honeypot field filled".to_string(),
+        })
+    }
+}
+
+/// 关键词/正则屏蔽规则，匹配内容和用户名，每条命中规则计50分
+pub struct BlocklistFilter;
+
+impl SpamFilter for BlocklistFilter {
+    fn check(&self, ctx: &SpamContext) -> Option<SpamHit> {
+        let content = ctx.content.to_lowercase();
+        let user = ctx.user.to_lowercase();
+        let matched: Vec<&str> = ctx
+            .block_rules
+            .iter()
+            .filter(|rule| {
+                if rule.is_regex {
+                    RegexBuilder::new(&rule.pattern)
+                        .case_insensitive(true)
+                        .build()
+                        .map(|re| re.is_match(ctx.content) || re.is_match(ctx.user))
+                        .unwrap_or(false)
+                } else {
+                    let pattern = rule.pattern.to_lowercase();
+                    content.contains(&pattern) || user.contains(&pattern)
+                }
+            })
+            .map(|rule| rule.pattern.as_str())
+            .collect();
+
+        if matched.is_empty() {
+            return None;
+        }
+        Some(SpamHit {
+            filter: "blocklist",
+            score: 50 * matched.len() as i32,
+            reason: format!("matched {}", matched.join(", ")),
+        })
+    }
+}
+
+/// 链接数量超过上限，每多一个链接计20分
+pub struct LinkCountFilter {
+    pub max_links: usize,
+}
+
+impl SpamFilter for LinkCountFilter {
+    fn check(&self, ctx: &SpamContext) -> Option<SpamHit> {
+        let links = LINK_REGEX.find_iter(ctx.content).count();
+        if links <= self.max_links {
+            return None;
+        }
+        Some(SpamHit {
+            filter: "links",
+            score: 20 * (links - self.max_links) as i32,
+            reason: format!("{} links (max {})", links, self.max_links),
+        })
+    }
+}
+
+/// 与同话题近期评论内容重复
+pub struct DuplicateFilter;
+
+// 归一化内容：忽略大小写和空白差异
+fn normalize(content: &str) -> String {
+    WHITESPACE_REGEX
+        .replace_all(content.trim(), " ")
+        .to_lowercase()
+}
+
+impl SpamFilter for DuplicateFilter {
+    fn check(&self, ctx: &SpamContext) -> Option<SpamHit> {
+        let content = normalize(ctx.content);
+        ctx.recent_contents
+            .iter()
+            .any(|recent| normalize(recent) == content)
+            .then(|| SpamHit {
+                filter: "duplicate",
+                score: 60,
+                reason: "duplicate content in topic".to_string(),
+            })
+    }
+}
+
+/// 默认过滤链，链接上限读取 `COMMENT_MAX_LINKS`，默认2
+pub fn default_filters() -> Vec<Box<dyn SpamFilter>> {
+    let max_links = env::var("COMMENT_MAX_LINKS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(2);
+
+    vec![
+        Box::new(HoneypotFilter),
+        Box::new(BlocklistFilter),
+        Box::new(LinkCountFilter { max_links }),
+        Box::new(DuplicateFilter),
+    ]
+}
+
+/// 依次运行过滤链，累计得分
+pub fn evaluate(filters: &[Box<dyn SpamFilter>], ctx: &SpamContext) -> SpamVerdict {
+    let hits: Vec<SpamHit> = filters.iter().filter_map(|f| f.check(ctx)).collect();
+    SpamVerdict {
+        score: hits.iter().map(|hit| hit.score).sum(),
+        hits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(pattern: &str, is_regex: bool) -> BlockRule {
+        BlockRule {
+            id: 0,
+            pattern: pattern.to_string(),
+            is_regex,
+            create_time: 0,
+        }
+    }
+
+    fn context<'a>(
+        content: &'a str,
+        recent: &'a [String],
+        rules: &'a [BlockRule],
+    ) -> SpamContext<'a> {
+        SpamContext {
+            content,
+            user: "visitor",
+            honeypot: None,
+            recent_contents: recent,
+            block_rules: rules,
+        }
+    }
+
+    #[test]
+    fn test_clean_comment_passes() {
+        let ctx = context("写得很好，学习了 https://wycode.cn", &[], &[]);
+        let verdict = evaluate(&default_filters(), &ctx);
+        assert_eq!(verdict.score, 0);
+        assert!(verdict.hits.is_empty());
+    }
+
+    #[test]
+    fn test_honeypot_hits() {
+        let mut ctx = context("hello", &[], &[]);
+        ctx.honeypot = Some("http://spam.example");
+        assert_eq!(HoneypotFilter.check(&ctx).unwrap().score, 100);
+        ctx.honeypot = Some("  ");
+        assert!(HoneypotFilter.check(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_blocklist_keyword_and_regex() {
+        let rules = vec![rule("Casino", false), rule(r"\d{11}", true), rule("(", true)];
+        let ctx = context("best casino, call 13800000000", &[], &rules);
+        let hit = BlocklistFilter.check(&ctx).unwrap();
+        // 非法正则被忽略
+        assert_eq!(hit.score, 100);
+    }
+
+    #[test]
+    fn test_link_count_limit() {
+        let filter = LinkCountFilter { max_links: 1 };
+        let ctx = context("http://a.com www.b.com https://c.com", &[], &[]);
+        assert_eq!(filter.check(&ctx).unwrap().score, 40);
+        let ctx = context("http://a.com", &[], &[]);
+        assert!(filter.check(&ctx).is_none());
+    }
+
+    #[test]
+    fn test_duplicate_ignores_case_and_whitespace() {
+        let recent = vec!["Nice   Post!".to_string()];
+        let ctx = context(" nice post! ", &recent, &[]);
+        assert!(DuplicateFilter.check(&ctx).is_some());
+    }
+}
@@ -0,0 +1,101 @@
+use chrono::{DateTime, SecondsFormat, TimeZone};
+use chrono_tz::Tz;
+use std::env;
+
+// 未设置时区时的默认时区，服务运行在UTC的容器中，不能依赖系统时区
+const DEFAULT_TIMEZONE: Tz = chrono_tz::Asia::Shanghai;
+
+/// 解析IANA时区名，如 `Asia/Shanghai`、`UTC`
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+    name.trim().parse::<Tz>().ok()
+}
+
+/// 默认时区：环境变量 `COMMENT_TIMEZONE`，未设置或无效时为 Asia/Shanghai
+pub fn default_timezone() -> Tz {
+    env::var("COMMENT_TIMEZONE")
+        .ok()
+        .and_then(|name| parse_timezone(&name))
+        .unwrap_or(DEFAULT_TIMEZONE)
+}
+
+/// 评论时间的显示方式
+pub struct TimeFormat {
+    pub tz: Tz,
+    /// 是否显示为相对时间，如“3 分钟前”
+    pub relative: bool,
+    /// 计算相对时间的当前时间（秒）
+    pub now: i64,
+}
+
+impl TimeFormat {
+    pub fn new(tz: Tz, relative: bool, now: i64) -> Self {
+        Self { tz, relative, now }
+    }
+
+    // 时间戳对应的时区时间，超出范围时按0处理
+    fn datetime(&self, ts: i64) -> DateTime<Tz> {
+        self.tz
+            .timestamp_opt(ts, 0)
+            .single()
+            .unwrap_or_else(|| self.tz.timestamp_opt(0, 0).unwrap())
+    }
+
+    /// 格式化为显示字符串；相对时间超过30天时显示日期时间
+    pub fn format(&self, ts: i64) -> String {
+        if self.relative
+            && let Some(relative) = relative_time(ts, self.now)
+        {
+            return relative;
+        }
+        self.datetime(ts).format("%Y/%m/%d %H:%M:%S").to_string()
+    }
+
+    /// 格式化为带时区偏移的ISO-8601字符串
+    pub fn iso(&self, ts: i64) -> String {
+        self.datetime(ts).to_rfc3339_opts(SecondsFormat::Secs, false)
+    }
+}
+
+/// 相对时间，如“刚刚”、“3 分钟前”；超过30天时返回None
+pub fn relative_time(ts: i64, now: i64) -> Option<String> {
+    let seconds = (now - ts).max(0);
+    match seconds {
+        0..60 => Some("刚刚".to_string()),
+        60..3600 => Some(format!("{} 分钟前", seconds / 60)),
+        3600..86400 => Some(format!("{} 小时前", seconds / 3600)),
+        86400..2592000 => Some(format!("{} 天前", seconds / 86400)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_in_timezone() {
+        let shanghai = TimeFormat::new(parse_timezone("Asia/Shanghai").unwrap(), false, 0);
+        assert_eq!(shanghai.format(0), "1970/01/01 08:00:00");
+        assert_eq!(shanghai.iso(0), "1970-01-01T08:00:00+08:00");
+
+        let utc = TimeFormat::new(parse_timezone("UTC").unwrap(), false, 0);
+        assert_eq!(utc.iso(1_577_934_245), "2020-01-02T03:04:05+00:00");
+        assert!(parse_timezone("Mars/Base").is_none());
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = 1_000_000;
+        assert_eq!(relative_time(now - 5, now).as_deref(), Some("刚刚"));
+        assert_eq!(relative_time(now + 5, now).as_deref(), Some("刚刚"));
+        assert_eq!(relative_time(now - 180, now).as_deref(), Some("3 分钟前"));
+        assert_eq!(relative_time(now - 7200, now).as_deref(), Some("2 小时前"));
+        assert_eq!(relative_time(now - 86400 * 3, now).as_deref(), Some("3 天前"));
+        assert_eq!(relative_time(now - 86400 * 31, now), None);
+
+        let created = 1_577_934_245;
+        let format = TimeFormat::new(parse_timezone("UTC").unwrap(), true, created + 86400 * 31);
+        assert_eq!(format.format(created + 86400 * 31 - 180), "3 分钟前");
+        assert_eq!(format.format(created), "2020/01/02 03:04:05");
+    }
+}
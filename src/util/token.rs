@@ -0,0 +1,87 @@
+use hmac::{Hmac, Mac};
+use lazy_static::lazy_static;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::env;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 未配置TOKEN_SECRET时使用的进程内随机密钥，重启后已签发的链接失效
+lazy_static! {
+    static ref FALLBACK_SECRET: String = {
+        eprintln!("⚠️ 未设置 TOKEN_SECRET，签名链接将在重启后失效");
+        Uuid::new_v4().to_string()
+    };
+}
+
+fn secret() -> String {
+    env::var("TOKEN_SECRET")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| FALLBACK_SECRET.clone())
+}
+
+fn new_mac() -> HmacSha256 {
+    HmacSha256::new_from_slice(secret().as_bytes()).expect("HMAC can take key of any size")
+}
+
+/// 使用服务端密钥（环境变量 `TOKEN_SECRET`）对数据做HMAC-SHA256签名，返回十六进制字符串
+pub fn sign(data: &str) -> String {
+    let mut mac = new_mac();
+    mac.update(data.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// 校验签名是否与数据匹配（常量时间比较）
+pub fn verify(data: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature) else {
+        return false;
+    };
+    let mut mac = new_mac();
+    mac.update(data.as_bytes());
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// 生成随机凭证（32字节，十六进制）
+pub fn random_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// 凭证的SHA-256哈希，数据库中只保存哈希
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signature = sign("unsubscribe:user@example.com");
+        assert!(verify("unsubscribe:user@example.com", &signature));
+    }
+
+    #[test]
+    fn test_verify_rejects_tampered_data() {
+        let signature = sign("unsubscribe:user@example.com");
+        assert!(!verify("unsubscribe:other@example.com", &signature));
+    }
+
+    #[test]
+    fn test_random_token_is_unique() {
+        let token = random_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, random_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_signature() {
+        assert!(!verify("data", "not-hex"));
+        assert!(!verify("data", ""));
+    }
+}
@@ -0,0 +1,423 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use std::env;
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::dao::comment::{Comment, STATUS_APPROVED};
+use crate::dao::webhook::{
+    DELIVERY_FAILED, DELIVERY_PENDING, DELIVERY_SUCCESS, DeliveryResult, WebhookDelivery,
+    claim_delivery, finish_delivery, get_due_deliveries, get_subscribed_webhooks, get_webhook,
+    insert_delivery,
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 单次投递请求超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// 首次重试的等待时间（秒），之后每次翻倍
+const RETRY_BASE_SECONDS: i64 = 30;
+// 重试等待时间上限（秒）
+const MAX_RETRY_DELAY: i64 = 6 * 3600;
+// 领取投递记录后的占用时间（秒），超过后其他任务可以重新领取；须长于请求超时，避免请求未结束就被重复投递
+const DELIVERY_LEASE_SECONDS: i64 = 60;
+const _: () = assert!(DELIVERY_LEASE_SECONDS > REQUEST_TIMEOUT.as_secs() as i64);
+// 每次处理的最大投递数
+const DELIVERY_BATCH_SIZE: i64 = 50;
+// 记录的错误信息最大长度（字符）
+const MAX_ERROR_LENGTH: usize = 500;
+
+// 唤醒投递任务，有新投递记录时不必等到下一次定时重试
+static DELIVERY_WAKER: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// 等待新的投递记录；唤醒时投递任务正在运行的，下次等待时立即返回
+pub async fn delivery_requested() {
+    DELIVERY_WAKER.notified().await;
+}
+
+/// Webhook事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    /// 发表新评论（不含垃圾评论）
+    Created,
+    /// 评论审核通过
+    Approved,
+    /// 评论被点赞
+    Liked,
+    /// 已公开的评论被删除、拒绝、标记为垃圾评论或因举报被隐藏
+    Deleted,
+}
+
+/// 可订阅的全部事件
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "comment.created",
+    "comment.approved",
+    "comment.liked",
+    "comment.deleted",
+];
+
+impl WebhookEvent {
+    /// 事件标识，用于订阅配置和请求头
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::Created => "comment.created",
+            WebhookEvent::Approved => "comment.approved",
+            WebhookEvent::Liked => "comment.liked",
+            WebhookEvent::Deleted => "comment.deleted",
+        }
+    }
+}
+
+// 投递的JSON内容
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    id: &'a str,
+    event: &'a str,
+    app: &'a str,
+    #[serde(rename = "createTime")]
+    create_time: i64,
+    comment: &'a Comment,
+}
+
+/// 评论状态变化对应的事件：变为公开时为approved，从公开变为其他状态时为deleted
+pub fn status_event(old_status: &str, new_status: &str) -> Option<WebhookEvent> {
+    if old_status == new_status {
+        None
+    } else if new_status == STATUS_APPROVED {
+        Some(WebhookEvent::Approved)
+    } else if old_status == STATUS_APPROVED {
+        Some(WebhookEvent::Deleted)
+    } else {
+        None
+    }
+}
+
+/// 请求签名：以订阅密钥对 `时间戳.请求体` 做HMAC-SHA256，格式为 `sha256=十六进制`
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// 最大投递次数，环境变量 `WEBHOOK_MAX_ATTEMPTS`，默认5次
+pub fn max_attempts() -> i64 {
+    env::var("WEBHOOK_MAX_ATTEMPTS")
+        .ok()
+        .and_then(|s| s.parse::<i64>().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(5)
+}
+
+/// 第attempts次投递失败后到下次重试的等待时间（秒），指数退避
+pub fn retry_delay(attempts: i64) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 20) as u32;
+    (RETRY_BASE_SECONDS * 2i64.pow(exponent)).min(MAX_RETRY_DELAY)
+}
+
+/// 为应用中订阅了该事件的Webhook创建投递记录，并唤醒投递任务
+///
+/// 返回创建的投递数。
+pub async fn dispatch(
+    pool: &SqlitePool,
+    event: WebhookEvent,
+    comment: &Comment,
+) -> Result<usize, String> {
+    let webhooks = get_subscribed_webhooks(pool, &comment.app, event.as_str())
+        .await
+        .map_err(|e| format!("error loading webhooks: {:?}", e))?;
+    if webhooks.is_empty() {
+        return Ok(0);
+    }
+
+    let now = Utc::now().timestamp();
+    for webhook in &webhooks {
+        let id = Uuid::new_v4().to_string();
+        let payload = serde_json::to_string(&WebhookPayload {
+            id: &id,
+            event: event.as_str(),
+            app: &comment.app,
+            create_time: now,
+            comment,
+        })
+        .map_err(|e| format!("error serializing webhook payload: {:?}", e))?;
+
+        let delivery = WebhookDelivery {
+            id,
+            webhook_id: webhook.id.clone(),
+            event: event.as_str().to_string(),
+            payload,
+            status: DELIVERY_PENDING.to_string(),
+            attempts: 0,
+            next_attempt_time: now,
+            response_status: None,
+            error: None,
+            create_time: now,
+            update_time: now,
+        };
+        insert_delivery(pool, &delivery)
+            .await
+            .map_err(|e| format!("error inserting webhook delivery: {:?}", e))?;
+    }
+
+    // 由唯一的投递任务发送，不等待投递完成
+    DELIVERY_WAKER.notify_one();
+
+    Ok(webhooks.len())
+}
+
+/// 发送一次投递请求，返回响应状态码；网络错误或超时时返回错误
+pub async fn send_delivery(
+    client: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    delivery: &WebhookDelivery,
+    timestamp: i64,
+) -> Result<u16, String> {
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Id", &delivery.id)
+        .header("X-Webhook-Event", &delivery.event)
+        .header("X-Webhook-Timestamp", timestamp.to_string())
+        .header(
+            "X-Webhook-Signature",
+            sign_payload(secret, timestamp, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(response.status().as_u16())
+}
+
+/// 投递所有已到时间的记录，失败的按退避时间重试，达到最大次数后标记为失败
+///
+/// 返回投递成功的数量。
+pub async fn deliver_due_webhooks(pool: &SqlitePool) -> Result<usize, String> {
+    let now = Utc::now().timestamp();
+    let deliveries = get_due_deliveries(pool, now, DELIVERY_BATCH_SIZE)
+        .await
+        .map_err(|e| format!("error loading webhook deliveries: {:?}", e))?;
+    if deliveries.is_empty() {
+        return Ok(0);
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("error building http client: {:?}", e))?;
+    let max_attempts = max_attempts();
+
+    let mut delivered = 0;
+    for delivery in deliveries {
+        // 按领取时的时间计算占用期限，前面的投递耗时不会缩短本次的占用时间
+        let claim_time = Utc::now().timestamp();
+        let claimed = claim_delivery(
+            pool,
+            &delivery.id,
+            claim_time,
+            claim_time + DELIVERY_LEASE_SECONDS,
+        )
+        .await
+        .map_err(|e| format!("error claiming webhook delivery: {:?}", e))?;
+        if !claimed {
+            continue;
+        }
+
+        let webhook = get_webhook(pool, &delivery.webhook_id)
+            .await
+            .map_err(|e| format!("error loading webhook: {:?}", e))?;
+        let attempts = delivery.attempts + 1;
+        let sent_time = Utc::now().timestamp();
+
+        // 订阅已停用或删除时不再重试
+        let (outcome, retry) = match &webhook {
+            Some(webhook) if webhook.enabled => (
+                send_delivery(&client, &webhook.url, &webhook.secret, &delivery, sent_time).await,
+                true,
+            ),
+            _ => (Err("webhook disabled".to_string()), false),
+        };
+
+        let result = match outcome {
+            Ok(status) if (200..300).contains(&status) => {
+                delivered += 1;
+                DeliveryResult {
+                    status: DELIVERY_SUCCESS,
+                    attempts,
+                    next_attempt_time: sent_time,
+                    response_status: Some(status as i64),
+                    error: None,
+                }
+            }
+            outcome => {
+                let (response_status, error) = match outcome {
+                    Ok(status) => (Some(status as i64), format!("HTTP {}", status)),
+                    Err(e) => (None, e.chars().take(MAX_ERROR_LENGTH).collect()),
+                };
+                let give_up = !retry || attempts >= max_attempts;
+                DeliveryResult {
+                    status: if give_up { DELIVERY_FAILED } else { DELIVERY_PENDING },
+                    attempts,
+                    next_attempt_time: sent_time + retry_delay(attempts),
+                    response_status,
+                    error: Some(error),
+                }
+            }
+        };
+
+        finish_delivery(pool, &delivery.id, &result, Utc::now().timestamp())
+            .await
+            .map_err(|e| format!("error updating webhook delivery: {:?}", e))?;
+    }
+
+    Ok(delivered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, http::HeaderMap, http::StatusCode, routing::post};
+    use std::sync::{Arc, Mutex};
+
+    fn delivery(payload: &str) -> WebhookDelivery {
+        WebhookDelivery {
+            id: "d1".to_string(),
+            webhook_id: "w1".to_string(),
+            event: "comment.created".to_string(),
+            payload: payload.to_string(),
+            status: DELIVERY_PENDING.to_string(),
+            attempts: 0,
+            next_attempt_time: 0,
+            response_status: None,
+            error: None,
+            create_time: 0,
+            update_time: 0,
+        }
+    }
+
+    // 启动本地HTTP服务模拟Webhook接收方，记录收到的请求头和请求体
+    async fn stand_in(status: StatusCode) -> (String, Arc<Mutex<Vec<(HeaderMap, String)>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let store = Arc::clone(&received);
+        let app = Router::new().route(
+            "/hook",
+            post(move |headers: HeaderMap, body: String| {
+                let store = Arc::clone(&store);
+                async move {
+                    store.lock().unwrap().push((headers, body));
+                    status
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (format!("http://{}/hook", addr), received)
+    }
+
+    #[tokio::test]
+    async fn test_send_delivery_signs_request() {
+        let (url, received) = stand_in(StatusCode::NO_CONTENT).await;
+        let client = reqwest::Client::new();
+        let payload = r#"{"event":"comment.created"}"#;
+
+        let status = send_delivery(&client, &url, "s3cret", &delivery(payload), 1_700_000_000)
+            .await
+            .unwrap();
+        assert_eq!(status, 204);
+
+        let received = received.lock().unwrap();
+        let (headers, body) = &received[0];
+        assert_eq!(body, payload);
+        assert_eq!(headers["x-webhook-id"], "d1");
+        assert_eq!(headers["x-webhook-event"], "comment.created");
+        assert_eq!(headers["x-webhook-timestamp"], "1700000000");
+        assert_eq!(
+            headers["x-webhook-signature"],
+            sign_payload("s3cret", 1_700_000_000, payload).as_str()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_delivery_reports_failure() {
+        let (url, _) = stand_in(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let client = reqwest::Client::new();
+        let status = send_delivery(&client, &url, "s", &delivery("{}"), 0).await;
+        assert_eq!(status, Ok(500));
+
+        // 接收方不可达
+        let unreachable =
+            send_delivery(&client, "http://127.0.0.1:1/hook", "s", &delivery("{}"), 0).await;
+        assert!(unreachable.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_runs_deliver_once() {
+        let pool = crate::dao::database::init_test_pool().await;
+        let (url, received) = stand_in(StatusCode::OK).await;
+        crate::dao::webhook::insert_webhook(
+            &pool,
+            &crate::dao::webhook::CommentWebhook {
+                id: "w1".to_string(),
+                app: "blog".to_string(),
+                url,
+                secret: "s".to_string(),
+                events: sqlx::types::Json(vec!["comment.created".to_string()]),
+                enabled: true,
+                create_time: 0,
+            },
+        )
+        .await
+        .unwrap();
+        crate::dao::webhook::insert_delivery(&pool, &delivery("{}"))
+            .await
+            .unwrap();
+
+        let (first, second) =
+            tokio::join!(deliver_due_webhooks(&pool), deliver_due_webhooks(&pool));
+        assert_eq!(first.unwrap() + second.unwrap(), 1);
+        assert_eq!(received.lock().unwrap().len(), 1);
+        assert_eq!(deliver_due_webhooks(&pool).await, Ok(0));
+    }
+
+    #[test]
+    fn test_sign_payload() {
+        let signature = sign_payload("secret", 1, "{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), 7 + 64);
+        assert_ne!(signature, sign_payload("secret", 2, "{}"));
+        assert_ne!(signature, sign_payload("other", 1, "{}"));
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(1), 30);
+        assert_eq!(retry_delay(2), 60);
+        assert_eq!(retry_delay(4), 240);
+        assert_eq!(retry_delay(100), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_status_event() {
+        assert_eq!(
+            status_event("pending", "approved"),
+            Some(WebhookEvent::Approved)
+        );
+        assert_eq!(
+            status_event("approved", "deleted"),
+            Some(WebhookEvent::Deleted)
+        );
+        assert_eq!(status_event("approved", "approved"), None);
+        assert_eq!(status_event("pending", "rejected"), None);
+    }
+}